@@ -0,0 +1,100 @@
+use rust_chess::*;
+use std::env;
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "Usage: bench [options]
+  --depth <n>         how deep to search each position (default 4)
+  --without <option>  leave one refinement out, can be given more than once:
+{options}
+  --threads <n>       how many threads search (default 1)
+  --plain             compare against plain alpha-beta instead of the same
+                      search with the moves unordered";
+
+// the usage with the option names filled in, wrapped under --without's text
+fn usage() -> String {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for name in SearchOptions::NAMES {
+        if line.len() + name.len() + 2 > 58 {
+            lines.push(format!("{:22}{},", "", line));
+            line.clear();
+        } else if !line.is_empty() {
+            line.push_str(", ");
+        }
+        line.push_str(name);
+    }
+    lines.push(format!("{:22}{}", "", line));
+    USAGE.replace("{options}", &lines.join("\n"))
+}
+
+// Searches the bench positions once as a baseline and once with the chosen
+// options, and prints the nodes each took.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut depth = 4;
+    let mut threads = 1;
+    let mut options = SearchOptions::default();
+    let mut baseline = ("unordered", SearchOptions::unordered());
+
+    let mut i = 0;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1).map(String::as_str)) {
+            ("--depth", Some(value)) => match value.parse() {
+                Ok(value) => depth = value,
+                Err(_) => fail(&format!("{} is not a depth", value)),
+            },
+            ("--threads", Some(value)) => match value.parse() {
+                Ok(value) if value > 0 => threads = value,
+                _ => fail(&format!("{} is not a number of threads", value)),
+            },
+            ("--without", Some(option)) => {
+                if !options.set(option, false) {
+                    fail(&format!("there is no option {}, the options are {}", option, SearchOptions::NAMES.join(", ")));
+                }
+            }
+            ("--plain", _) => {
+                baseline = ("plain", SearchOptions::plain());
+                i += 1;
+                continue;
+            }
+            ("-h" | "--help", _) => {
+                println!("{}", usage());
+                return;
+            }
+            _ => fail(&usage()),
+        }
+        i += 2;
+    }
+
+    println!("{:>4} {:>12} {:>12} {:>8}", "", baseline.0, "searched", "saved");
+    let (mut baseline_total, mut searched_total) = (0, 0);
+    let started = Instant::now();
+    for (n, fen) in BENCH_POSITIONS.iter().enumerate() {
+        let board = Board::from_fen(fen).expect("the bench positions are valid");
+        let before = Searcher::with_options(baseline.1).search(&board, SearchLimits::depth(depth)).nodes;
+        let mut searcher = Searcher::with_options(options);
+        searcher.set_threads(threads);
+        let after = searcher.search(&board, SearchLimits::depth(depth)).nodes;
+        println!("{:>4} {:>12} {:>12} {:>7.1}%", n + 1, before, after, saved(before, after));
+        baseline_total += before;
+        searched_total += after;
+    }
+    println!(
+        "{:>4} {:>12} {:>12} {:>7.1}%",
+        "all",
+        baseline_total,
+        searched_total,
+        saved(baseline_total, searched_total)
+    );
+    println!("{:.1}s at depth {}", started.elapsed().as_secs_f64(), depth);
+}
+
+fn saved(before: u64, after: u64) -> f64 {
+    100.0 * (1.0 - after as f64 / before.max(1) as f64)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
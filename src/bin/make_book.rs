@@ -0,0 +1,83 @@
+use rust_chess::*;
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "Usage: make_book <games.pgn> <book.bin> [options]
+  --max-ply <n>    how many half moves of each game go in (default 20)
+  --min-games <n>  leave out moves played fewer times (default 1)
+  --white          only white's moves
+  --black          only black's moves";
+
+// Builds a Polyglot opening book from a pgn collection.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut files = Vec::new();
+    let mut builder = BookBuilder::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--max-ply" | "--min-games" => {
+                let value = match args.get(i + 1).and_then(|value| value.parse::<usize>().ok()) {
+                    Some(value) => value,
+                    None => fail(&format!("{} needs a number", args[i])),
+                };
+                if args[i] == "--max-ply" {
+                    builder.max_ply = value;
+                } else {
+                    builder.min_games = value as u32;
+                }
+                i += 1;
+            }
+            "--white" => builder.only_white = true,
+            "--black" => builder.only_black = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            file => files.push(file.to_string()),
+        }
+        i += 1;
+    }
+    let (pgn_file, book_file) = match files.as_slice() {
+        [pgn_file, book_file] => (pgn_file, book_file),
+        _ => fail(USAGE),
+    };
+
+    let text = match fs::read_to_string(pgn_file) {
+        Ok(text) => text,
+        Err(e) => fail(&format!("Could not read {}: {}", pgn_file, e)),
+    };
+    let mut read = 0;
+    let mut skipped = 0;
+    for game in read_pgn_games(&text) {
+        match game {
+            Ok(game) => {
+                builder.add_game(&game);
+                read += 1;
+            }
+            Err(e) => {
+                eprintln!("Skipping a game: {}", e);
+                skipped += 1;
+            }
+        }
+    }
+
+    let book = builder.build();
+    if let Err(e) = book.save(book_file) {
+        fail(&format!("Could not write {}: {}", book_file, e));
+    }
+    println!(
+        "Read {} games ({} skipped), wrote {} entries to {}",
+        read,
+        skipped,
+        book.len(),
+        book_file
+    );
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
@@ -0,0 +1,241 @@
+use rust_chess::*;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process;
+
+const USAGE: &str = "Usage: match_runner --engine <spec> --engine <spec> [options]
+  --engine <spec>     one of the two players, as comma separated settings:
+                      name=<name>, without=<option> (as many as wanted, the
+                      options are bench's), depth=<n>, nodes=<n>, hash=<mb>,
+                      threads=<n>, eval=<file> (weights written by tune).
+                      The first engine is the one being tested.
+                      cmd=<program> plays a UCI engine instead, with its
+                      options set by option.<name>=<value>, and depth and
+                      nodes used when there is no clock.
+  --games <n>         how many games to play (default 100), an even number
+                      plays every opening with both colors
+  --tc <control>      the time control, like 10+0.1 or 40/60 (default 10+0.1),
+                      or none to play by depth or nodes alone
+  --openings <file>   start from the positions of an EPD file, or the games
+                      of a PGN file, in turn
+  --draw <rule>       adjudicate a draw once both sides score within score of
+                      equal for moves moves each after move number after:
+                      after=40,moves=8,score=10
+  --resign <rule>     adjudicate a win once both sides agree one is at least
+                      score ahead for moves moves each: moves=3,score=600
+  --max-moves <n>     adjudicate a draw after this many moves each
+  --pgn <file>        add each finished game to a PGN file
+  --sprt <test>       stop once a sequential probability ratio test is
+                      decided, looked at after each pair of games:
+                      elo0=0,elo1=5,alpha=0.05,beta=0.05";
+
+// Plays two configurations of the engine against each other and reports the
+// Elo difference between them.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut specs = Vec::new();
+    let mut settings = MatchSettings {
+        games: 100,
+        time_control: Some("10+0.1".parse().expect("the default time control reads")),
+        event: "rust_chess match".to_string(),
+        ..Default::default()
+    };
+    let mut openings = Vec::new();
+    let mut pgn_file = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "-h" || args[i] == "--help" {
+            println!("{}", USAGE);
+            return;
+        }
+        let value = match args.get(i + 1) {
+            Some(value) => value.as_str(),
+            None => fail(USAGE),
+        };
+        match args[i].as_str() {
+            "--engine" => specs.push(value.to_string()),
+            "--games" => settings.games = number(value),
+            "--tc" if value == "none" => settings.time_control = None,
+            "--tc" => settings.time_control = Some(value.parse().unwrap_or_else(|e| fail(&format!("{}", e)))),
+            "--openings" => openings = read_openings(value),
+            "--draw" => {
+                let rule = settings_of(value);
+                settings.adjudication.draw = Some(DrawRule {
+                    after: number(rule("after").unwrap_or("0")),
+                    moves: number(rule("moves").unwrap_or("8")),
+                    score: number(rule("score").unwrap_or("10")),
+                });
+            }
+            "--resign" => {
+                let rule = settings_of(value);
+                settings.adjudication.resign = Some(ResignRule {
+                    moves: number(rule("moves").unwrap_or("3")),
+                    score: number(rule("score").unwrap_or("600")),
+                });
+            }
+            "--max-moves" => settings.adjudication.max_moves = Some(number(value)),
+            "--pgn" => pgn_file = Some(value.to_string()),
+            "--sprt" => {
+                let test = settings_of(value);
+                settings.sprt = Some(Sprt::new(
+                    number(test("elo0").unwrap_or("0")),
+                    number(test("elo1").unwrap_or("5")),
+                    number(test("alpha").unwrap_or("0.05")),
+                    number(test("beta").unwrap_or("0.05")),
+                ));
+            }
+            _ => fail(USAGE),
+        }
+        i += 2;
+    }
+    if specs.len() != 2 {
+        fail("a match needs two engines");
+    }
+    let clock = settings.time_control.is_some();
+    let mut first = engine(&specs[0], clock);
+    let mut second = engine(&specs[1], clock);
+
+    let names = format!("{} vs {}", first.name(), second.name());
+    let result = play_match(&mut *first, &mut *second, &openings, &settings, |game, score| {
+        println!(
+            "Game {} {} - {}: {} ({})",
+            game.round,
+            game.white,
+            game.black,
+            game.result(),
+            game.termination().map_or("unfinished".to_string(), |termination| termination.to_string())
+        );
+        println!("Score of {}: {} [{:.3}] {}", names, score, score.ratio(), score.games());
+        if let Some(file) = &pgn_file {
+            let written = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)
+                .and_then(|mut file| writeln!(file, "{}", game.to_pgn()));
+            if let Err(e) = written {
+                fail(&format!("could not write {}: {}", file, e));
+            }
+        }
+    });
+    let score = result.unwrap_or_else(|e| fail(&format!("an opening does not play: {}", e)));
+
+    println!("Elo difference: {:.1} +/- {:.1}", score.elo(), score.elo_error());
+    if let Some(sprt) = settings.sprt {
+        let (lower, upper) = sprt.bounds();
+        println!(
+            "SPRT elo0={} elo1={}: LLR {:.2} ({:.2}, {:.2}), {}",
+            sprt.elo0,
+            sprt.elo1,
+            sprt.llr(&score),
+            lower,
+            upper,
+            sprt.status(&score)
+        );
+    }
+}
+
+// name=new,without=lmr,depth=6 or name=other,cmd=/path/to/engine,option.Hash=64
+fn engine(spec: &str, clock: bool) -> Box<dyn Player> {
+    let settings: Vec<(&str, &str)> = spec
+        .split(',')
+        .map(|setting| setting.split_once('=').unwrap_or_else(|| fail(&format!("cannot read engine setting {:?}", setting))))
+        .collect();
+    let setting = |name: &str| settings.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+    let name = setting("name").unwrap_or_else(|| fail("every engine needs a name="));
+    let depth = setting("depth").map(number);
+    let nodes = setting("nodes").map(number);
+
+    if let Some(program) = setting("cmd") {
+        let mut engine = UciEngine::start(program, &[]).unwrap_or_else(|e| fail(&format!("{}: {}", program, e)));
+        engine.set_name(name);
+        if depth.is_some() || nodes.is_some() {
+            engine.limits = GoLimits { depth, nodes, ..Default::default() };
+        }
+        for (key, value) in &settings {
+            match *key {
+                "name" | "cmd" | "depth" | "nodes" => (),
+                _ => match key.strip_prefix("option.") {
+                    Some(option) => engine
+                        .set_option(option, value)
+                        .unwrap_or_else(|e| fail(&format!("{}: {}", name, e))),
+                    None => fail(&format!("a UCI engine has no setting {}", key)),
+                },
+            }
+        }
+        engine.is_ready().unwrap_or_else(|e| fail(&format!("{}: {}", name, e)));
+        if !clock && depth.is_none() && nodes.is_none() {
+            eprintln!("{} plays to depth {} without a clock", name, engine.limits.depth.unwrap_or_default());
+        }
+        return Box::new(engine);
+    }
+
+    let mut options = SearchOptions::default();
+    let mut searcher = Searcher::new();
+    for (key, value) in &settings {
+        match *key {
+            "name" | "depth" | "nodes" => (),
+            "without" => {
+                if !options.set(value, false) {
+                    fail(&format!("there is no option {}, the options are {}", value, SearchOptions::NAMES.join(", ")));
+                }
+            }
+            "hash" => searcher.set_hash_size(number(value)),
+            "threads" => searcher.set_threads(number(value)),
+            "eval" => {
+                let text = fs::read_to_string(value).unwrap_or_else(|e| fail(&format!("could not read {}: {}", value, e)));
+                searcher.set_eval_params(text.parse().unwrap_or_else(|e| fail(&format!("{}: {}", value, e))));
+            }
+            _ => fail(&format!("cannot read engine setting {}={}", key, value)),
+        }
+    }
+    searcher.options = options;
+    if !clock && depth.is_none() && nodes.is_none() {
+        fail(&format!("{} needs a depth or nodes to play without a clock", name));
+    }
+    let mut player = SearcherPlayer::new(name, searcher);
+    player.depth = depth;
+    player.nodes = nodes;
+    Box::new(player)
+}
+
+fn read_openings(file: &str) -> Vec<Opening> {
+    let text = fs::read_to_string(file).unwrap_or_else(|e| fail(&format!("could not read {}: {}", file, e)));
+    let openings: Vec<Opening> = if file.to_ascii_lowercase().ends_with(".pgn") {
+        read_pgn_games(&text)
+            .into_iter()
+            .map(|game| game.map(|game| Opening::from_game(&game)).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()
+    } else {
+        // only the position is used, not the operations
+        read_epd(&text)
+            .into_iter()
+            .map(|epd| epd.map(|epd| Opening::new(epd.board)).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()
+    }
+    .unwrap_or_else(|e| fail(&format!("bad opening in {}: {}", file, e)));
+    if openings.is_empty() {
+        fail(&format!("there are no openings in {}", file));
+    }
+    openings
+}
+
+// the value of a setting in a comma separated list of name=value
+fn settings_of<'a>(list: &'a str) -> impl Fn(&str) -> Option<&'a str> + 'a {
+    move |name| {
+        list.split(',')
+            .filter_map(|setting| setting.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+fn number<T: std::str::FromStr>(text: &str) -> T {
+    text.parse().unwrap_or_else(|_| fail(&format!("{} is not a number", text)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
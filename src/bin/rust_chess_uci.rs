@@ -0,0 +1,7 @@
+use rust_chess::Uci;
+use std::io;
+
+// The engine for chess GUIs and match runners that speak UCI.
+fn main() {
+    Uci::new(io::stdout()).run(io::stdin().lock());
+}
@@ -0,0 +1,115 @@
+use rust_chess::*;
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: test_suite <file.epd> [options]
+  --time <seconds>    how long to search each position (default 1)
+  --depth <n>         search each position to this depth instead
+  --hash <mb>         the size of the transposition table
+  --threads <n>       how many threads search (default 1)
+  --quiet             only print the positions that failed and the total";
+
+// Searches every position of an EPD test suite, like WAC or STS, and counts
+// how many the engine finds the bm of, or avoids the am of.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut file = None;
+    let mut limits = SearchLimits::time(Duration::from_secs(1));
+    let mut searcher = Searcher::new();
+    let mut quiet = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1).map(String::as_str)) {
+            ("--time", Some(value)) => match value.parse::<f64>() {
+                Ok(secs) if secs.is_finite() && secs > 0.0 => limits = SearchLimits::time(Duration::from_secs_f64(secs)),
+                _ => fail(&format!("{} is not a number of seconds", value)),
+            },
+            ("--depth", Some(value)) => match value.parse() {
+                Ok(depth) => limits = SearchLimits::depth(depth),
+                Err(_) => fail(&format!("{} is not a depth", value)),
+            },
+            ("--hash", Some(value)) => match value.parse() {
+                Ok(megabytes) => searcher.set_hash_size(megabytes),
+                Err(_) => fail(&format!("{} is not a number of megabytes", value)),
+            },
+            ("--threads", Some(value)) => match value.parse() {
+                Ok(threads) if threads > 0 => searcher.set_threads(threads),
+                _ => fail(&format!("{} is not a number of threads", value)),
+            },
+            ("--quiet", _) => {
+                quiet = true;
+                i += 1;
+                continue;
+            }
+            ("-h" | "--help", _) => {
+                println!("{}", USAGE);
+                return;
+            }
+            (name, _) if file.is_none() && !name.starts_with('-') => {
+                file = Some(name.to_string());
+                i += 1;
+                continue;
+            }
+            _ => fail(USAGE),
+        }
+        i += 2;
+    }
+    let file = file.unwrap_or_else(|| fail(USAGE));
+
+    let text = fs::read_to_string(&file).unwrap_or_else(|e| fail(&format!("could not read {}: {}", file, e)));
+    let positions: Vec<Epd> = read_epd(&text)
+        .into_iter()
+        .enumerate()
+        .map(|(n, epd)| epd.unwrap_or_else(|e| fail(&format!("position {} of {}: {}", n + 1, file, e))))
+        .collect();
+
+    let mut failed = Vec::new();
+    let mut n = 0;
+    let result = run_suite(&mut searcher, &positions, limits, |position, outcome, score| {
+        n += 1;
+        let id = position.id().map(String::from).unwrap_or_else(|| n.to_string());
+        let played = outcome.best_move.map_or("none".to_string(), |mv| position.board.move_to_san(&mv));
+        let mut wanted = Vec::new();
+        for opcode in ["bm", "am"] {
+            if let Some(moves) = position.operation(opcode) {
+                wanted.push(format!("{} {}", opcode, moves.join(" ")));
+            }
+        }
+        let verdict = if outcome.solved { "ok" } else { "failed" };
+        if !quiet || !outcome.solved {
+            println!(
+                "{:>4} {:<16} {:<8} {:<20} {:<6} {}/{}",
+                n,
+                id,
+                played,
+                wanted.join(", "),
+                verdict,
+                score.solved,
+                score.total()
+            );
+        }
+        if !outcome.solved {
+            failed.push(id);
+        }
+    });
+    let score = result.unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+
+    println!(
+        "Solved {} of {} ({:.1}%), failed {}",
+        score.solved,
+        score.total(),
+        100.0 * score.solved as f64 / score.total().max(1) as f64,
+        score.failed
+    );
+    if !failed.is_empty() {
+        println!("Failed: {}", failed.join(" "));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
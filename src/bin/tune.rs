@@ -0,0 +1,102 @@
+use rust_chess::*;
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "Usage: tune <positions> [options]
+  --iterations <n>    how many steps to take (default 1000)
+  --rate <cp>         how far a weight moves in the first steps (default 1)
+  --k <k>             the scale of the sigmoid, fitted to the starting
+                      weights when not given
+  --start <file>      start from the weights in a file written by tune
+                      rather than the built in ones
+  --out <file>        where to write the tuned weights (default eval.txt)
+  --rust              write them as the Rust source of DEFAULT_EVAL_PARAMS,
+                      to paste over the one in eval.rs
+
+The positions are one to a line, a fen or EPD position then the result of
+its game: 1-0, 0-1, 1/2-1/2 or a number like [0.5], or c9 \"1-0\";. They
+should be quiet, as the evaluation is tuned without a search.";
+
+// Tunes the evaluation weights to a file of positions labelled with the
+// results of their games, by Texel's method.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut file = None;
+    let mut iterations = 1000;
+    let mut rate = 1.0;
+    let mut k = None;
+    let mut params = EvalParams::default();
+    let mut out = "eval.txt".to_string();
+    let mut rust = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1).map(String::as_str)) {
+            ("--iterations", Some(value)) => iterations = number(value),
+            ("--rate", Some(value)) => rate = number(value),
+            ("--k", Some(value)) => k = Some(number(value)),
+            ("--start", Some(value)) => {
+                let text = fs::read_to_string(value).unwrap_or_else(|e| fail(&format!("could not read {}: {}", value, e)));
+                params = text.parse().unwrap_or_else(|e| fail(&format!("{}: {}", value, e)));
+            }
+            ("--out", Some(value)) => out = value.to_string(),
+            ("--rust", _) => {
+                rust = true;
+                i += 1;
+                continue;
+            }
+            ("-h" | "--help", _) => {
+                println!("{}", USAGE);
+                return;
+            }
+            (name, _) if file.is_none() && !name.starts_with('-') => {
+                file = Some(name.to_string());
+                i += 1;
+                continue;
+            }
+            _ => fail(USAGE),
+        }
+        i += 2;
+    }
+    let file = file.unwrap_or_else(|| fail(USAGE));
+
+    let text = fs::read_to_string(&file).unwrap_or_else(|e| fail(&format!("could not read {}: {}", file, e)));
+    let positions: Vec<(Board, f64)> = read_labelled(&text)
+        .into_iter()
+        .enumerate()
+        .map(|(n, position)| position.unwrap_or_else(|e| fail(&format!("position {} of {}: {}", n + 1, file, e))))
+        .collect();
+    if positions.is_empty() {
+        fail(&format!("there are no positions in {}", file));
+    }
+
+    let mut tuner = Tuner::new(&positions);
+    match k {
+        Some(k) => tuner.k = k,
+        None => {
+            tuner.fit_k(&params);
+        }
+    }
+    println!("{} positions, k {:.4}, error {:.6}", tuner.len(), tuner.k, tuner.error(&params));
+
+    let tuned = tuner.tune(&params, iterations, rate, |step, error| {
+        if step % 50 == 0 || step == iterations {
+            println!("step {:>6} error {:.6}", step, error);
+        }
+    });
+    println!("tuned error {:.6}", tuner.error(&tuned));
+
+    let written = if rust { tuned.to_rust() } else { tuned.to_string() };
+    fs::write(&out, written).unwrap_or_else(|e| fail(&format!("could not write {}: {}", out, e)));
+    println!("wrote {}", out);
+}
+
+fn number<T: std::str::FromStr>(text: &str) -> T {
+    text.parse().unwrap_or_else(|_| fail(&format!("{} is not a number", text)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
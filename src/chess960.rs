@@ -0,0 +1,80 @@
+use crate::{build_piece, Board, Color, PieceType};
+
+// where the two knights go among the five squares left after the bishops and queen
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+// Back rank for a chess960 start position, using the standard numbering
+// where 518 is the normal RNBQKBNR.
+pub fn chess960_back_rank(index: u16) -> Option<[PieceType; 8]> {
+    if index >= 960 {
+        return None;
+    }
+    let mut rank: [Option<PieceType>; 8] = [None; 8];
+    let mut n = index as usize;
+
+    rank[2 * (n % 4) + 1] = Some(PieceType::Bishop);
+    n /= 4;
+    rank[2 * (n % 4)] = Some(PieceType::Bishop);
+    n /= 4;
+
+    let empty = |rank: &[Option<PieceType>; 8]| -> Vec<usize> {
+        (0..8).filter(|&file| rank[file].is_none()).collect()
+    };
+
+    rank[empty(&rank)[n % 6]] = Some(PieceType::Queen);
+    n /= 6;
+
+    let free = empty(&rank);
+    let (first, second) = KNIGHT_PLACEMENTS[n];
+    rank[free[first]] = Some(PieceType::Knight);
+    rank[free[second]] = Some(PieceType::Knight);
+
+    // the king always goes between the two rooks
+    let free = empty(&rank);
+    rank[free[0]] = Some(PieceType::Rook(false));
+    rank[free[1]] = Some(PieceType::King(false));
+    rank[free[2]] = Some(PieceType::Rook(false));
+
+    Some(rank.map(|piece| piece.unwrap()))
+}
+
+impl Board {
+    pub fn chess960(index: u16) -> Option<Board> {
+        let back_rank = chess960_back_rank(index)?;
+        let mut board = Board {
+            board: [[None; 8]; 8],
+            ..Default::default()
+        };
+        for (file, piece_type) in back_rank.into_iter().enumerate() {
+            board.board[0][file] = build_piece(piece_type, Color::Black);
+            board.board[7][file] = build_piece(piece_type, Color::White);
+        }
+        board.board[1] = [build_piece(PieceType::Pawn(false), Color::Black); 8];
+        board.board[6] = [build_piece(PieceType::Pawn(false), Color::White); 8];
+        Some(board)
+    }
+    // Whether castling here needs chess960's rules, with a castling king
+    // off the e file or a castling rook off the a and h files.
+    pub fn is_chess960(&self) -> bool {
+        [(Color::White, 7), (Color::Black, 0)].into_iter().any(|(color, rank)| {
+            let row = &self.board[rank];
+            let king = (0..8).find(|&file| row[file] == build_piece(PieceType::King(false), color));
+            king.is_some_and(|king| {
+                (0..8).any(|file| {
+                    row[file] == build_piece(PieceType::Rook(false), color) && (king != 4 || (file != 0 && file != 7))
+                })
+            })
+        })
+    }
+}
@@ -0,0 +1,472 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{Board, Color, GameResult, PieceType};
+
+// Where a clock reads the time from. The reading only has to grow, what it
+// counts from does not matter.
+pub trait TimeSource: fmt::Debug + Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+// the real time, counted from when the source was made
+#[derive(Debug, Clone, Copy)]
+pub struct RealTime {
+    start: Instant,
+}
+
+impl RealTime {
+    pub fn new() -> RealTime {
+        RealTime { start: Instant::now() }
+    }
+}
+
+impl Default for RealTime {
+    fn default() -> RealTime {
+        RealTime::new()
+    }
+}
+
+impl TimeSource for RealTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// A time source that only moves when told to. Clones share the same time, so
+// keep one to advance and hand another to the clock.
+#[derive(Debug, Clone, Default)]
+pub struct ManualTime {
+    millis: Arc<AtomicU64>,
+}
+
+impl ManualTime {
+    pub fn new() -> ManualTime {
+        ManualTime::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.millis.fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    pub fn set(&self, to: Duration) {
+        self.millis.store(to.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.millis.load(Ordering::SeqCst))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Delay {
+    #[default]
+    None,
+    // the time used is given back afterwards, up to the delay
+    Bronstein(Duration),
+    // the clock waits this long each move before it starts counting down
+    Simple(Duration),
+}
+
+// One stage of a time control. A period without a move count lasts the rest
+// of the game, the last period with one repeats for as long as the game goes on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Period {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub increment: Duration,
+    pub delay: Delay,
+}
+
+impl Period {
+    pub fn new(time: Duration) -> Period {
+        Period {
+            moves: None,
+            time,
+            increment: Duration::ZERO,
+            delay: Delay::None,
+        }
+    }
+}
+
+// Always has at least one period.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeControl {
+    periods: Vec<Period>,
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> TimeControl {
+        TimeControl { periods: vec![Period::new(time)] }
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> TimeControl {
+        TimeControl {
+            periods: vec![Period { increment, ..Period::new(time) }],
+        }
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> TimeControl {
+        TimeControl {
+            periods: vec![Period { delay: Delay::Bronstein(delay), ..Period::new(time) }],
+        }
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> TimeControl {
+        TimeControl {
+            periods: vec![Period { delay: Delay::Simple(delay), ..Period::new(time) }],
+        }
+    }
+
+    pub fn with_periods(periods: Vec<Period>) -> Result<TimeControl, TimeControlError> {
+        if periods.is_empty() {
+            return Err(TimeControlError::NoPeriods);
+        }
+        Ok(TimeControl { periods })
+    }
+
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+
+    fn period(&self, index: usize) -> &Period {
+        &self.periods[index.min(self.periods.len() - 1)]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeControlError {
+    Unreadable(String),
+    NoPeriods,
+}
+
+impl fmt::Display for TimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeControlError::Unreadable(text) => write!(f, "could not read time control {:?}", text),
+            TimeControlError::NoPeriods => write!(f, "a time control needs at least one period"),
+        }
+    }
+}
+
+impl std::error::Error for TimeControlError {}
+
+// Written the way the pgn TimeControl tag has it, in seconds with periods split
+// by colons: "40/5400+30:1800+30" is 40 moves in 90 minutes then 30 minutes for
+// the rest, with 30 seconds added every move. A delay is written as d5 for a
+// simple delay or b5 for Bronstein.
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let periods: Vec<String> = self
+            .periods
+            .iter()
+            .map(|period| {
+                let mut text = String::new();
+                if let Some(moves) = period.moves {
+                    text.push_str(&format!("{}/", moves));
+                }
+                text.push_str(&seconds(period.time));
+                if !period.increment.is_zero() {
+                    text.push_str(&format!("+{}", seconds(period.increment)));
+                }
+                match period.delay {
+                    Delay::None => (),
+                    Delay::Simple(delay) => text.push_str(&format!("d{}", seconds(delay))),
+                    Delay::Bronstein(delay) => text.push_str(&format!("b{}", seconds(delay))),
+                }
+                text
+            })
+            .collect();
+        write!(f, "{}", periods.join(":"))
+    }
+}
+
+fn seconds(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        duration.as_secs().to_string()
+    } else {
+        format!("{}", duration.as_secs_f64())
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = TimeControlError;
+
+    fn from_str(s: &str) -> Result<TimeControl, TimeControlError> {
+        let error = || TimeControlError::Unreadable(s.to_string());
+        let parse_seconds = |text: &str| -> Result<Duration, TimeControlError> {
+            text.parse::<f64>()
+                .ok()
+                .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                .map(Duration::from_secs_f64)
+                .ok_or_else(error)
+        };
+
+        let mut periods = Vec::new();
+        for text in s.trim().split(':') {
+            let (moves, rest) = match text.split_once('/') {
+                Some((moves, rest)) => (Some(moves.parse::<u32>().ok().filter(|&n| n > 0).ok_or_else(error)?), rest),
+                None => (None, text),
+            };
+            let (rest, delay) = match rest.find(['d', 'b']) {
+                Some(i) => {
+                    let delay = parse_seconds(&rest[i + 1..])?;
+                    let delay = if rest[i..].starts_with('d') {
+                        Delay::Simple(delay)
+                    } else {
+                        Delay::Bronstein(delay)
+                    };
+                    (&rest[..i], delay)
+                }
+                None => (rest, Delay::None),
+            };
+            let (time, increment) = match rest.split_once('+') {
+                Some((time, increment)) => (parse_seconds(time)?, parse_seconds(increment)?),
+                None => (parse_seconds(rest)?, Duration::ZERO),
+            };
+            periods.push(Period { moves, time, increment, delay });
+        }
+        if periods.is_empty() || periods.iter().all(|period| period.time.is_zero()) {
+            return Err(error());
+        }
+        Ok(TimeControl { periods })
+    }
+}
+
+// A two sided game clock. The side whose clock runs finishes its move with
+// press, which charges the time used, applies the delay and increment and
+// starts the other side's clock.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    period: [usize; 2],
+    moves_in_period: [u32; 2],
+    running: Option<Color>,
+    started_at: Duration,
+    flagged: Option<Color>,
+    source: Arc<dyn TimeSource>,
+}
+
+fn index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Clock {
+        Clock::with_time_source(control, Arc::new(RealTime::new()))
+    }
+
+    pub fn with_time_source(control: TimeControl, source: Arc<dyn TimeSource>) -> Clock {
+        let time = control.period(0).time;
+        Clock {
+            control,
+            remaining: [time; 2],
+            period: [0; 2],
+            moves_in_period: [0; 2],
+            running: None,
+            started_at: Duration::ZERO,
+            flagged: None,
+            source,
+        }
+    }
+
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn running(&self) -> Option<Color> {
+        self.running
+    }
+
+    // starts the clock of the given side, or keeps it going if it already runs
+    pub fn start(&mut self, color: Color) {
+        if self.flagged.is_some() || self.running == Some(color) {
+            return;
+        }
+        self.pause();
+        self.running = Some(color);
+        self.started_at = self.source.now();
+    }
+
+    // stops the clock without finishing the move, the time used so far is kept
+    pub fn pause(&mut self) {
+        if let Some(color) = self.running.take() {
+            let used = self.source.now().saturating_sub(self.started_at);
+            let charged = self.charged(color, used);
+            let i = index(color);
+            if charged >= self.remaining[i] {
+                self.remaining[i] = Duration::ZERO;
+                self.flagged = Some(color);
+            } else {
+                self.remaining[i] -= charged;
+            }
+        }
+    }
+
+    // Ends the move of the side whose clock runs. Gives false when that side
+    // ran out of time before pressing, the clock then stays stopped.
+    pub fn press(&mut self) -> bool {
+        let color = match self.running.take() {
+            Some(color) => color,
+            None => return self.flagged.is_none(),
+        };
+        let i = index(color);
+        let used = self.source.now().saturating_sub(self.started_at);
+        let charged = self.charged(color, used);
+        if charged >= self.remaining[i] {
+            self.remaining[i] = Duration::ZERO;
+            self.flagged = Some(color);
+            return false;
+        }
+
+        let period = *self.control.period(self.period[i]);
+        self.remaining[i] -= charged;
+        if let Delay::Bronstein(delay) = period.delay {
+            self.remaining[i] += used.min(delay);
+        }
+        self.remaining[i] += period.increment;
+
+        self.moves_in_period[i] += 1;
+        if period.moves == Some(self.moves_in_period[i]) {
+            self.period[i] += 1;
+            self.moves_in_period[i] = 0;
+            self.remaining[i] += self.control.period(self.period[i]).time;
+        }
+
+        self.running = Some(color.opposite());
+        self.started_at = self.source.now();
+        true
+    }
+
+    // how much of the time used this move counts against the clock
+    fn charged(&self, color: Color, used: Duration) -> Duration {
+        match self.control.period(self.period[index(color)]).delay {
+            Delay::Simple(delay) => used.saturating_sub(delay),
+            _ => used,
+        }
+    }
+
+    // the time a side has left, counting the move in progress
+    pub fn remaining(&self, color: Color) -> Duration {
+        let i = index(color);
+        if self.running == Some(color) {
+            let used = self.source.now().saturating_sub(self.started_at);
+            self.remaining[i].saturating_sub(self.charged(color, used))
+        } else {
+            self.remaining[i]
+        }
+    }
+
+    // moves left to make before the next time control, if the period has one
+    pub fn moves_to_go(&self, color: Color) -> Option<u32> {
+        let i = index(color);
+        self.control
+            .period(self.period[i])
+            .moves
+            .map(|moves| moves - self.moves_in_period[i])
+    }
+
+    pub fn increment(&self, color: Color) -> Duration {
+        self.control.period(self.period[index(color)]).increment
+    }
+
+    // Puts a side's clock back where a saved game left it, from the time it
+    // had left and how many moves it had made.
+    pub fn restore(&mut self, color: Color, remaining: Duration, moves_made: u32) {
+        let i = index(color);
+        let mut period = 0;
+        let mut moves_left = moves_made;
+        while let Some(moves) = self.control.period(period).moves {
+            if moves_left < moves {
+                break;
+            }
+            moves_left -= moves;
+            period += 1;
+        }
+        self.period[i] = period;
+        self.moves_in_period[i] = moves_left;
+        self.remaining[i] = remaining;
+        if remaining.is_zero() {
+            self.flagged = Some(color);
+        }
+    }
+
+    // the side that ran out of time, noticed even before it presses
+    pub fn flagged(&self) -> Option<Color> {
+        self.flagged.or_else(|| {
+            self.running
+                .filter(|&color| self.remaining(color).is_zero())
+        })
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for color in [Color::White, Color::Black] {
+            let marker = if self.running == Some(color) { "*" } else { " " };
+            write!(f, "{}{} {}", marker, color, format_time(self.remaining(color)))?;
+            if self.flagged() == Some(color) {
+                write!(f, " (flag)")?;
+            }
+            if color == Color::White {
+                write!(f, "   ")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// h:mm:ss, or m:ss.t when under a minute is left
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else if secs >= 60 {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    } else {
+        format!("0:{:02}.{}", secs, time.subsec_millis() / 100)
+    }
+}
+
+// Running out of time is only a draw when the other side could never mate
+// whatever is played: it has a bare king, or a king and one minor piece
+// against a bare king. With anything for the flagged side to block with, even
+// a lone knight can mate.
+fn can_mate(board: &Board, color: Color) -> bool {
+    let mut minors = 0;
+    let mut opponent_bare = true;
+    for piece in board.board.iter().flatten().flatten() {
+        if piece.color != color {
+            opponent_bare &= matches!(piece.piece_type, PieceType::King(_));
+            continue;
+        }
+        match piece.piece_type {
+            PieceType::King(_) => (),
+            PieceType::Bishop | PieceType::Knight => minors += 1,
+            _ => return true,
+        }
+    }
+    minors > 1 || (minors == 1 && !opponent_bare)
+}
+
+// result_of, with a fallen flag losing the game
+pub fn result_with_clock(board: &Board, clock: &Clock) -> GameResult {
+    let result = crate::result_of(board);
+    if result != GameResult::Ongoing {
+        return result;
+    }
+    match clock.flagged() {
+        Some(color) if !can_mate(board, color.opposite()) => GameResult::Draw,
+        Some(color) => GameResult::win_for(color.opposite()),
+        None => GameResult::Ongoing,
+    }
+}
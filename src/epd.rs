@@ -0,0 +1,261 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Board, FenError, Move, MoveError, SearchLimits, SearchResult, Searcher};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    BadFen(FenError),
+    BadOperation(String),
+    BadMove { opcode: String, error: MoveError },
+    NoSolution(String), // a test position with neither bm nor am
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpdError::BadFen(e) => write!(f, "{}", e),
+            EpdError::BadOperation(text) => write!(f, "could not read operation {:?}", text),
+            EpdError::BadMove { opcode, error } => write!(f, "{}: {}", opcode, error),
+            EpdError::NoSolution(id) => write!(f, "{} has no bm or am to test against", id),
+        }
+    }
+}
+
+impl Error for EpdError {}
+
+// One line of an EPD file: the first four fields of a fen, then operations
+// like bm Qg6; or id "WAC.001"; each an opcode and its operands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epd {
+    pub board: Board,
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    pub fn new(board: Board) -> Epd {
+        Epd { board, operations: Vec::new() }
+    }
+
+    // the operands of the first operation with this opcode
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(name, _)| name == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.operation("id")?.first().map(String::as_str)
+    }
+
+    // The moves an operation lists, in SAN as the standard has them, though
+    // coordinates like e2e4 are taken too. None listed is an empty list.
+    pub fn moves(&self, opcode: &str) -> Result<Vec<Move>, EpdError> {
+        let operands = self.operation(opcode).unwrap_or_default();
+        operands
+            .iter()
+            .map(|text| {
+                self.board
+                    .parse_san(text)
+                    .or_else(|error| self.board.parse_coordinates(text).map_err(|_| error))
+                    .map_err(|error| EpdError::BadMove { opcode: opcode.to_string(), error })
+            })
+            .collect()
+    }
+
+    pub fn best_moves(&self) -> Result<Vec<Move>, EpdError> {
+        self.moves("bm")
+    }
+
+    pub fn avoid_moves(&self) -> Result<Vec<Move>, EpdError> {
+        self.moves("am")
+    }
+
+    // A move solves the position when it is one of the best moves and none of
+    // the ones to avoid.
+    pub fn is_solved_by(&self, mv: &Move) -> Result<bool, EpdError> {
+        let best = self.best_moves()?;
+        let avoid = self.avoid_moves()?;
+        Ok((best.is_empty() || best.contains(mv)) && !avoid.contains(mv))
+    }
+}
+
+impl FromStr for Epd {
+    type Err = EpdError;
+
+    // Takes the four position fields, then the halfmove and fullmove numbers
+    // if a full fen was given, then the operations. The hmvc and fmvn opcodes
+    // set those numbers too.
+    fn from_str(s: &str) -> Result<Epd, EpdError> {
+        let mut rest = s.trim();
+        let mut fields = Vec::new();
+        while fields.len() < 6 {
+            let (field, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let is_number = !field.is_empty() && field.chars().all(|c| c.is_ascii_digit());
+            if field.is_empty() || (fields.len() >= 4 && !is_number) {
+                break;
+            }
+            fields.push(field);
+            rest = after.trim_start();
+        }
+        if fields.len() < 4 {
+            return Err(EpdError::BadFen(FenError::WrongFieldCount(fields.len())));
+        }
+
+        let operations = parse_operations(rest)?;
+        let number = |opcode: &str| -> Option<&str> {
+            let operands = operations.iter().find(|(name, _)| name == opcode)?;
+            operands.1.first().map(String::as_str)
+        };
+        let halfmove = number("hmvc").or(fields.get(4).copied()).unwrap_or("0");
+        let fullmove = number("fmvn").or(fields.get(5).copied()).unwrap_or("1");
+        let fen = format!("{} {} {}", fields[..4].join(" "), halfmove, fullmove);
+        let board = Board::from_fen(&fen).map_err(EpdError::BadFen)?;
+        Ok(Epd { board, operations })
+    }
+}
+
+// the position fields and then each operation, quoting operands with spaces
+impl fmt::Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fen = self.board.to_fen();
+        let fields: Vec<&str> = fen.split(' ').take(4).collect();
+        write!(f, "{}", fields.join(" "))?;
+        for (opcode, operands) in &self.operations {
+            write!(f, " {}", opcode)?;
+            for operand in operands {
+                let plain = !operand.is_empty() && !operand.contains(|c: char| c.is_whitespace() || c == ';' || c == '"');
+                if plain && opcode != "id" {
+                    write!(f, " {}", operand)?;
+                } else {
+                    write!(f, " \"{}\"", operand)?;
+                }
+            }
+            write!(f, ";")?;
+        }
+        Ok(())
+    }
+}
+
+// Splits the operations at the semicolons outside quotes. A quoted operand
+// keeps its spaces and loses its quotes.
+fn parse_operations(text: &str) -> Result<Vec<(String, Vec<String>)>, EpdError> {
+    let mut operations = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    fn end_word(word: &mut String, quoted: &mut bool, words: &mut Vec<String>) {
+        if !word.is_empty() || *quoted {
+            words.push(std::mem::take(word));
+        }
+        *quoted = false;
+    }
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            _ if in_quotes => word.push(c),
+            ';' => {
+                end_word(&mut word, &mut quoted, &mut words);
+                end_operation(&mut words, &mut operations)?;
+            }
+            _ if c.is_whitespace() => end_word(&mut word, &mut quoted, &mut words),
+            _ => word.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(EpdError::BadOperation(text.to_string()));
+    }
+    // the last semicolon is often left off
+    end_word(&mut word, &mut quoted, &mut words);
+    end_operation(&mut words, &mut operations)?;
+    Ok(operations)
+}
+
+fn end_operation(words: &mut Vec<String>, operations: &mut Vec<(String, Vec<String>)>) -> Result<(), EpdError> {
+    let mut operation = std::mem::take(words).into_iter();
+    match operation.next() {
+        Some(opcode) if is_opcode(&opcode) => operations.push((opcode, operation.collect())),
+        Some(opcode) => return Err(EpdError::BadOperation(opcode)),
+        None => (),
+    }
+    Ok(())
+}
+
+// opcodes start with a letter and go on in letters, digits and underscores
+fn is_opcode(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_alphabetic())
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Reads every position in an EPD file, skipping blank lines and ones
+// starting with #.
+pub fn read_epd(text: &str) -> Vec<Result<Epd, EpdError>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+// How one test position went: the move the search settled on and whether it
+// was a solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiteOutcome {
+    pub best_move: Option<Move>,
+    pub solved: bool,
+    pub search: SearchResult,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SuiteScore {
+    pub solved: usize,
+    pub failed: usize,
+}
+
+impl SuiteScore {
+    pub fn total(&self) -> usize {
+        self.solved + self.failed
+    }
+}
+
+// Searches every position of a test suite with the same limits, from a fresh
+// table each time, telling on_position how each went along with the score so
+// far. Every position needs a bm or an am, checked before any is searched.
+pub fn run_suite(
+    searcher: &mut Searcher,
+    positions: &[Epd],
+    limits: SearchLimits,
+    mut on_position: impl FnMut(&Epd, &SuiteOutcome, &SuiteScore),
+) -> Result<SuiteScore, EpdError> {
+    for (n, position) in positions.iter().enumerate() {
+        if position.best_moves()?.is_empty() && position.avoid_moves()?.is_empty() {
+            let id = position.id().map(String::from).unwrap_or_else(|| format!("position {}", n + 1));
+            return Err(EpdError::NoSolution(id));
+        }
+    }
+
+    let mut score = SuiteScore::default();
+    for position in positions {
+        searcher.new_game();
+        let search = searcher.search(&position.board, limits);
+        let solved = match &search.best_move {
+            Some(mv) => position.is_solved_by(mv)?,
+            None => false,
+        };
+        if solved {
+            score.solved += 1;
+        } else {
+            score.failed += 1;
+        }
+        let outcome = SuiteOutcome { best_move: search.best_move, solved, search };
+        on_position(position, &outcome, &score);
+    }
+    Ok(score)
+}
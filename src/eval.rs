@@ -0,0 +1,383 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Mul};
+use std::str::FromStr;
+
+use crate::{Board, Color, PieceType};
+
+// Material in centipawns, as exchanges and move ordering count it. These are
+// the built in piece values and stay so when the evaluation is given others.
+pub const PAWN_VALUE: i32 = DEFAULT_EVAL_PARAMS.piece_values[0];
+pub const KNIGHT_VALUE: i32 = DEFAULT_EVAL_PARAMS.piece_values[1];
+pub const BISHOP_VALUE: i32 = DEFAULT_EVAL_PARAMS.piece_values[2];
+pub const ROOK_VALUE: i32 = DEFAULT_EVAL_PARAMS.piece_values[3];
+pub const QUEEN_VALUE: i32 = DEFAULT_EVAL_PARAMS.piece_values[4];
+
+// The weights of the evaluation, which the tuner adjusts. The square
+// bonuses are as white sees the board, laid out like the board array with the
+// eighth rank first, and black reads them upside down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    pub piece_values: [i32; 5], // pawn, knight, bishop, rook, queen
+    pub pawn_table: [[i32; 8]; 8],
+    pub knight_table: [[i32; 8]; 8],
+    pub bishop_table: [[i32; 8]; 8],
+    pub rook_table: [[i32; 8]; 8],
+    pub queen_table: [[i32; 8]; 8],
+    pub king_middlegame_table: [[i32; 8]; 8], // while there are pieces about
+    pub king_endgame_table: [[i32; 8]; 8],
+}
+
+#[rustfmt::skip]
+pub const DEFAULT_EVAL_PARAMS: EvalParams = EvalParams {
+    piece_values: [100, 320, 330, 500, 900],
+    pawn_table: [
+        [  0,   0,   0,   0,   0,   0,   0,   0],
+        [ 50,  50,  50,  50,  50,  50,  50,  50],
+        [ 10,  10,  20,  30,  30,  20,  10,  10],
+        [  5,   5,  10,  25,  25,  10,   5,   5],
+        [  0,   0,   0,  20,  20,   0,   0,   0],
+        [  5,  -5, -10,   0,   0, -10,  -5,   5],
+        [  5,  10,  10, -20, -20,  10,  10,   5],
+        [  0,   0,   0,   0,   0,   0,   0,   0],
+    ],
+    knight_table: [
+        [-50, -40, -30, -30, -30, -30, -40, -50],
+        [-40, -20,   0,   0,   0,   0, -20, -40],
+        [-30,   0,  10,  15,  15,  10,   0, -30],
+        [-30,   5,  15,  20,  20,  15,   5, -30],
+        [-30,   0,  15,  20,  20,  15,   0, -30],
+        [-30,   5,  10,  15,  15,  10,   5, -30],
+        [-40, -20,   0,   5,   5,   0, -20, -40],
+        [-50, -40, -30, -30, -30, -30, -40, -50],
+    ],
+    bishop_table: [
+        [-20, -10, -10, -10, -10, -10, -10, -20],
+        [-10,   0,   0,   0,   0,   0,   0, -10],
+        [-10,   0,   5,  10,  10,   5,   0, -10],
+        [-10,   5,   5,  10,  10,   5,   5, -10],
+        [-10,   0,  10,  10,  10,  10,   0, -10],
+        [-10,  10,  10,  10,  10,  10,  10, -10],
+        [-10,   5,   0,   0,   0,   0,   5, -10],
+        [-20, -10, -10, -10, -10, -10, -10, -20],
+    ],
+    rook_table: [
+        [  0,   0,   0,   0,   0,   0,   0,   0],
+        [  5,  10,  10,  10,  10,  10,  10,   5],
+        [ -5,   0,   0,   0,   0,   0,   0,  -5],
+        [ -5,   0,   0,   0,   0,   0,   0,  -5],
+        [ -5,   0,   0,   0,   0,   0,   0,  -5],
+        [ -5,   0,   0,   0,   0,   0,   0,  -5],
+        [ -5,   0,   0,   0,   0,   0,   0,  -5],
+        [  0,   0,   0,   5,   5,   0,   0,   0],
+    ],
+    queen_table: [
+        [-20, -10, -10,  -5,  -5, -10, -10, -20],
+        [-10,   0,   0,   0,   0,   0,   0, -10],
+        [-10,   0,   5,   5,   5,   5,   0, -10],
+        [ -5,   0,   5,   5,   5,   5,   0,  -5],
+        [  0,   0,   5,   5,   5,   5,   0,  -5],
+        [-10,   5,   5,   5,   5,   5,   0, -10],
+        [-10,   0,   5,   0,   0,   0,   0, -10],
+        [-20, -10, -10,  -5,  -5, -10, -10, -20],
+    ],
+    // the king hides behind its pawns while there are pieces about
+    king_middlegame_table: [
+        [-30, -40, -40, -50, -50, -40, -40, -30],
+        [-30, -40, -40, -50, -50, -40, -40, -30],
+        [-30, -40, -40, -50, -50, -40, -40, -30],
+        [-30, -40, -40, -50, -50, -40, -40, -30],
+        [-20, -30, -30, -40, -40, -30, -30, -20],
+        [-10, -20, -20, -20, -20, -20, -20, -10],
+        [ 20,  20,   0,   0,   0,   0,  20,  20],
+        [ 20,  30,  10,   0,   0,  10,  30,  20],
+    ],
+    // and walks to the middle once they are gone
+    king_endgame_table: [
+        [-50, -40, -30, -20, -20, -30, -40, -50],
+        [-30, -20, -10,   0,   0, -10, -20, -30],
+        [-30, -10,  20,  30,  30,  20, -10, -30],
+        [-30, -10,  30,  40,  40,  30, -10, -30],
+        [-30, -10,  30,  40,  40,  30, -10, -30],
+        [-30, -10,  20,  30,  30,  20, -10, -30],
+        [-30, -30,   0,   0,   0,   0, -30, -30],
+        [-50, -30, -30, -30, -30, -30, -30, -50],
+    ],
+};
+
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn(_) => PAWN_VALUE,
+        PieceType::Knight => KNIGHT_VALUE,
+        PieceType::Bishop => BISHOP_VALUE,
+        PieceType::Rook(_) => ROOK_VALUE,
+        PieceType::Queen => QUEEN_VALUE,
+        PieceType::King(_) => 0,
+    }
+}
+
+// Material and piece placement in centipawns, from the point of view of the
+// side to move.
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_with(board, &DEFAULT_EVAL_PARAMS)
+}
+
+// the same with weights other than the built in ones
+pub fn evaluate_with(board: &Board, params: &EvalParams) -> i32 {
+    let score = evaluate_pieces(placed_pieces(board), |index| params.weight(index));
+    if board.side_to_move() == Color::White {
+        score
+    } else {
+        -score
+    }
+}
+
+// A piece as the evaluation sees it: its type, whether it is white's, and
+// its square in the tables, as white sees the board.
+pub(crate) type PlacedPiece = (PieceType, bool, usize);
+
+pub(crate) fn placed_pieces(board: &Board) -> impl Iterator<Item = PlacedPiece> + Clone + '_ {
+    board.board.iter().enumerate().flat_map(|(rank, row)| {
+        row.iter().enumerate().filter_map(move |(file, piece)| {
+            piece.map(|piece| {
+                let white = piece.color == Color::White;
+                let table_rank = if white { rank } else { 7 - rank };
+                (piece.piece_type, white, table_rank * 8 + file)
+            })
+        })
+    })
+}
+
+// The evaluation for white, given the weight at each index of to_vec. It
+// takes whole weights for the search and fractional ones for the tuner, so
+// both score a position the same way.
+pub(crate) fn evaluate_pieces<W>(pieces: impl Iterator<Item = PlacedPiece> + Clone, weight: impl Fn(usize) -> W) -> W
+where
+    W: Copy + PartialOrd + Add<Output = W> + Mul<Output = W> + From<i8>,
+{
+    let endgame = is_endgame(pieces.clone(), &weight);
+    features(pieces, endgame).fold(W::from(0), |score, (index, sign)| score + W::from(sign) * weight(index))
+}
+
+// the endgame starts once the queens are off or little else is left
+pub(crate) fn is_endgame<W>(pieces: impl Iterator<Item = PlacedPiece>, weight: impl Fn(usize) -> W) -> bool
+where
+    W: Copy + PartialOrd + Add<Output = W> + From<i8>,
+{
+    let non_pawn_material = pieces
+        .filter(|(piece_type, _, _)| !matches!(piece_type, PieceType::Pawn(_) | PieceType::King(_)))
+        .fold(W::from(0), |total, (piece_type, _, _)| total + weight(piece_index(piece_type)));
+    let rook = weight(piece_index(PieceType::Rook(true)));
+    let bishop = weight(piece_index(PieceType::Bishop));
+    non_pawn_material <= rook + rook + bishop + bishop
+}
+
+// The evaluation is a sum of weights: for each piece its value, unless it is
+// a king, and its table square. These are their indices in to_vec, each with
+// 1 when it is white's and -1 when it is black's.
+pub(crate) fn features(pieces: impl Iterator<Item = PlacedPiece>, endgame: bool) -> impl Iterator<Item = (usize, i8)> {
+    pieces.flat_map(move |(piece_type, white, square)| {
+        let sign = if white { 1 } else { -1 };
+        let (value, table) = weight_index(piece_type, endgame, square);
+        value.into_iter().chain([table]).map(move |index| (index, sign))
+    })
+}
+
+// the names of the tables in the order they are kept, as the config file has them
+const TABLE_NAMES: [&str; 7] = [
+    "pawn_table",
+    "knight_table",
+    "bishop_table",
+    "rook_table",
+    "queen_table",
+    "king_middlegame_table",
+    "king_endgame_table",
+];
+
+impl EvalParams {
+    // how many single weights there are, the piece values then every square of every table
+    pub const COUNT: usize = 5 + 7 * 64;
+
+    pub fn piece_value(&self, piece_type: PieceType) -> i32 {
+        match piece_type {
+            PieceType::King(_) => 0,
+            piece_type => self.piece_values[piece_index(piece_type)],
+        }
+    }
+
+    // the weight at an index of to_vec
+    fn weight(&self, index: usize) -> i32 {
+        match index.checked_sub(5) {
+            None => self.piece_values[index],
+            Some(square) => self.tables()[square / 64][square % 64 / 8][square % 8],
+        }
+    }
+
+    fn tables(&self) -> [&[[i32; 8]; 8]; 7] {
+        [
+            &self.pawn_table,
+            &self.knight_table,
+            &self.bishop_table,
+            &self.rook_table,
+            &self.queen_table,
+            &self.king_middlegame_table,
+            &self.king_endgame_table,
+        ]
+    }
+
+    fn tables_mut(&mut self) -> [&mut [[i32; 8]; 8]; 7] {
+        [
+            &mut self.pawn_table,
+            &mut self.knight_table,
+            &mut self.bishop_table,
+            &mut self.rook_table,
+            &mut self.queen_table,
+            &mut self.king_middlegame_table,
+            &mut self.king_endgame_table,
+        ]
+    }
+
+    // Every weight in one list, laid out as weight_index says.
+    pub fn to_vec(&self) -> Vec<i32> {
+        let mut values = self.piece_values.to_vec();
+        for table in self.tables() {
+            values.extend(table.iter().flatten());
+        }
+        values
+    }
+
+    // the other way round, from a list of COUNT weights
+    pub fn from_slice(values: &[i32]) -> Result<EvalParams, EvalParamsError> {
+        if values.len() != EvalParams::COUNT {
+            return Err(EvalParamsError(format!(
+                "there are {} weights, not {}",
+                values.len(),
+                EvalParams::COUNT
+            )));
+        }
+        let mut params = DEFAULT_EVAL_PARAMS;
+        params.piece_values.copy_from_slice(&values[..5]);
+        for (n, table) in params.tables_mut().into_iter().enumerate() {
+            for (square, value) in values[5 + n * 64..5 + (n + 1) * 64].iter().enumerate() {
+                table[square / 8][square % 8] = *value;
+            }
+        }
+        Ok(params)
+    }
+
+    // The weights as the Rust source of DEFAULT_EVAL_PARAMS, to paste over
+    // the one in eval.rs.
+    pub fn to_rust(&self) -> String {
+        let values: Vec<String> = self.piece_values.iter().map(i32::to_string).collect();
+        let mut source = String::from("#[rustfmt::skip]\npub const DEFAULT_EVAL_PARAMS: EvalParams = EvalParams {\n");
+        source.push_str(&format!("    piece_values: [{}],\n", values.join(", ")));
+        for (name, table) in TABLE_NAMES.iter().zip(self.tables()) {
+            match *name {
+                "king_middlegame_table" => source.push_str("    // the king hides behind its pawns while there are pieces about\n"),
+                "king_endgame_table" => source.push_str("    // and walks to the middle once they are gone\n"),
+                _ => (),
+            }
+            source.push_str(&format!("    {}: [\n", name));
+            for row in table {
+                let row: Vec<String> = row.iter().map(|value| format!("{:>3}", value)).collect();
+                source.push_str(&format!("        [{}],\n", row.join(", ")));
+            }
+            source.push_str("    ],\n");
+        }
+        source.push_str("};\n");
+        source
+    }
+}
+
+// The index in to_vec of the piece value or table square the weight of a
+// piece comes from, the square as white sees it.
+fn weight_index(piece_type: PieceType, endgame: bool, table_square: usize) -> (Option<usize>, usize) {
+    let value = match piece_type {
+        PieceType::King(_) => None,
+        piece_type => Some(piece_index(piece_type)),
+    };
+    (value, 5 + table_index(piece_type, endgame) * 64 + table_square)
+}
+
+// where a piece's value is in piece_values
+fn piece_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn(_) => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook(_) => 3,
+        PieceType::Queen => 4,
+        PieceType::King(_) => unreachable!("kings have no value and tables of their own"),
+    }
+}
+
+fn table_index(piece_type: PieceType, endgame: bool) -> usize {
+    match piece_type {
+        PieceType::King(_) if endgame => 6,
+        PieceType::King(_) => 5,
+        piece_type => piece_index(piece_type),
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> EvalParams {
+        DEFAULT_EVAL_PARAMS
+    }
+}
+
+// The config file: piece_values and its five numbers, then each table's name
+// and its 64 numbers, eighth rank first. Lines starting with # are comments.
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self.piece_values.iter().map(i32::to_string).collect();
+        writeln!(f, "piece_values {}", values.join(" "))?;
+        for (name, table) in TABLE_NAMES.iter().zip(self.tables()) {
+            writeln!(f, "{}", name)?;
+            for row in table {
+                let row: Vec<String> = row.iter().map(|value| format!("{:>4}", value)).collect();
+                writeln!(f, "{}", row.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParamsError(pub String);
+
+impl fmt::Display for EvalParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bad evaluation weights: {}", self.0)
+    }
+}
+
+impl Error for EvalParamsError {}
+
+// Weights left out keep their built in values.
+impl FromStr for EvalParams {
+    type Err = EvalParamsError;
+
+    fn from_str(s: &str) -> Result<EvalParams, EvalParamsError> {
+        let mut values = DEFAULT_EVAL_PARAMS.to_vec();
+        let mut words = s
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace);
+        while let Some(name) = words.next() {
+            let (start, count) = match TABLE_NAMES.iter().position(|table| *table == name) {
+                Some(n) => (5 + n * 64, 64),
+                None if name == "piece_values" => (0, 5),
+                None => return Err(EvalParamsError(format!("there are no weights called {}", name))),
+            };
+            for value in &mut values[start..start + count] {
+                let word = words
+                    .next()
+                    .ok_or_else(|| EvalParamsError(format!("{} needs {} numbers", name, count)))?;
+                *value = word
+                    .parse()
+                    .map_err(|_| EvalParamsError(format!("{} in {} is not a number", word, name)))?;
+            }
+        }
+        EvalParams::from_slice(&values)
+    }
+}
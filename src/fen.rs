@@ -0,0 +1,291 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{
+    array_notation_to_chess_notation, build_piece, parse_square, Board, Color, Piece, PieceType,
+    PositionError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    BadPlacement(String),
+    BadSideToMove(String),
+    BadCastling(String),
+    BadEnPessant(String),
+    BadMoveNumber(String),
+    IllegalPosition(PositionError),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) => {
+                write!(f, "expected 4 to 6 fields in fen, found {}", count)
+            }
+            FenError::BadPlacement(field) => write!(f, "bad piece placement: {}", field),
+            FenError::BadSideToMove(field) => write!(f, "bad side to move: {}", field),
+            FenError::BadCastling(field) => write!(f, "bad castling rights: {}", field),
+            FenError::BadEnPessant(field) => write!(f, "bad en passant square: {}", field),
+            FenError::BadMoveNumber(field) => write!(f, "bad move number: {}", field),
+            FenError::IllegalPosition(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Board {
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for (rank, row) in self.board.iter().enumerate() {
+            let mut empty = 0;
+            for square in row {
+                match square {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.get_piece_as_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank != 7 {
+                placement.push('/');
+            }
+        }
+
+        let side = if self.turn == 0 { "w" } else { "b" };
+
+        let en_pessant = match self.en_pessant_square {
+            Some(square) => array_notation_to_chess_notation(square),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            side,
+            self.castling_rights_fen(false),
+            en_pessant,
+            self.halfmove_clock,
+            u32::from(self.turn_number) + 1
+        )
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+
+        let mut board = Board {
+            board: parse_placement(fields[0])?,
+            ..Default::default()
+        };
+
+        board.turn = match fields[1] {
+            "w" => 0,
+            "b" => 1,
+            other => return Err(FenError::BadSideToMove(other.to_string())),
+        };
+
+        board.set_castling_rights_fen(fields[2])?;
+
+        if fields[3] != "-" {
+            let square = parse_square(fields[3])
+                .ok_or_else(|| FenError::BadEnPessant(fields[3].to_string()))?;
+            board.set_en_pessant_square(Some(square))?;
+        }
+
+        if let Some(halfmove) = fields.get(4) {
+            board.halfmove_clock = halfmove
+                .parse()
+                .map_err(|_| FenError::BadMoveNumber(halfmove.to_string()))?;
+        }
+        if let Some(fullmove) = fields.get(5) {
+            let fullmove: u16 = fullmove
+                .parse()
+                .map_err(|_| FenError::BadMoveNumber(fullmove.to_string()))?;
+            board.turn_number = fullmove.saturating_sub(1);
+        }
+
+        board.validate().map_err(FenError::IllegalPosition)?;
+        Ok(board)
+    }
+
+    // Same as to_fen but with castling rights given as the files of the rooks,
+    // the Shredder-FEN form used for chess960.
+    pub fn to_shredder_fen(&self) -> String {
+        let mut fields: Vec<String> = self.to_fen().split(' ').map(String::from).collect();
+        fields[2] = self.castling_rights_fen(true);
+        fields.join(" ")
+    }
+
+    // Replaces the castling rights with the ones in a fen castling field.
+    pub fn set_castling_rights(&mut self, rights: &str) -> Result<(), FenError> {
+        let mut cleared = self.clone();
+        for piece in cleared.board.iter_mut().flatten().flatten() {
+            piece.piece_type = match piece.piece_type {
+                PieceType::King(_) => PieceType::King(true),
+                PieceType::Rook(_) => PieceType::Rook(true),
+                other => other,
+            };
+        }
+        cleared.set_castling_rights_fen(rights)?;
+        self.board = cleared.board;
+        Ok(())
+    }
+
+    // Sets the square a pawn of the side to move could capture onto en passant.
+    pub fn set_en_pessant_square(&mut self, square: Option<(usize, usize)>) -> Result<(), FenError> {
+        self.can_en_pessant = [(-1, -1), (-1, -1)];
+        self.en_pessant_square = None;
+        let (rank, file) = match square {
+            Some(square) => square,
+            None => return Ok(()),
+        };
+        // the pawn that moved two sits one square past the target, seen from its own side
+        let (pawn_rank, pawn_color) = match (rank, self.turn) {
+            (2, 0) => (3, Color::Black),
+            (5, 1) => (4, Color::White),
+            _ => return Err(FenError::BadEnPessant(array_notation_to_chess_notation((rank, file)))),
+        };
+        self.check_en_pessant(pawn_color, (pawn_rank, file));
+        self.en_pessant_square = Some((rank, file));
+        Ok(())
+    }
+
+    // Castling rights live in the moved flags of the kings and rooks, a right
+    // exists for every unmoved rook that shares a back rank with its unmoved king.
+    // Normally this is written as KQkq, using the rook's file only when it is not
+    // the outermost rook on that side (X-FEN), or always with shredder.
+    fn castling_rights_fen(&self, shredder: bool) -> String {
+        let mut rights = String::new();
+        for (color, rank) in [(Color::White, 7), (Color::Black, 0)] {
+            let king_file = match self.unmoved_king_file(color, rank) {
+                Some(file) => file,
+                None => continue,
+            };
+            let outermost_short = (king_file + 1..8).rev().find(|&file| self.is_rook(color, rank, file));
+            let outermost_long = (0..king_file).find(|&file| self.is_rook(color, rank, file));
+
+            let mut color_rights = Vec::new();
+            let files = (king_file + 1..8).rev().chain((0..king_file).rev());
+            for file in files.filter(|&file| self.is_unmoved_rook(color, rank, file)) {
+                let right = if shredder {
+                    (b'A' + file as u8) as char
+                } else if Some(file) == outermost_short {
+                    'K'
+                } else if Some(file) == outermost_long {
+                    'Q'
+                } else {
+                    (b'A' + file as u8) as char
+                };
+                color_rights.push(right);
+            }
+            for right in color_rights {
+                rights.push(if color.is_white() { right } else { right.to_ascii_lowercase() });
+            }
+        }
+        if rights.is_empty() {
+            rights.push('-');
+        }
+        rights
+    }
+
+    // Reads KQkq, Shredder-FEN file letters, or a mix of both as X-FEN allows.
+    fn set_castling_rights_fen(&mut self, field: &str) -> Result<(), FenError> {
+        let bad_castling = || FenError::BadCastling(field.to_string());
+        if field == "-" {
+            return Ok(());
+        }
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(bad_castling());
+        }
+
+        for right in field.chars() {
+            let (color, rank) = if right.is_ascii_uppercase() { (Color::White, 7) } else { (Color::Black, 0) };
+            let king_file = (0..8)
+                .find(|&file| {
+                    matches!(self.board[rank][file], Some(Piece { piece_type: PieceType::King(_), color: c }) if c == color)
+                })
+                .ok_or_else(bad_castling)?;
+            let rook_file = match right.to_ascii_uppercase() {
+                'K' => (king_file + 1..8).rev().find(|&file| self.is_rook(color, rank, file)),
+                'Q' => (0..king_file).find(|&file| self.is_rook(color, rank, file)),
+                letter @ 'A'..='H' => Some((letter as u8 - b'A') as usize).filter(|&file| self.is_rook(color, rank, file)),
+                _ => None,
+            }
+            .ok_or_else(bad_castling)?;
+
+            self.board[rank][king_file] = build_piece(PieceType::King(false), color);
+            self.board[rank][rook_file] = build_piece(PieceType::Rook(false), color);
+        }
+        Ok(())
+    }
+
+    fn is_rook(&self, color: Color, rank: usize, file: usize) -> bool {
+        matches!(self.board[rank][file], Some(Piece { piece_type: PieceType::Rook(_), color: c }) if c == color)
+    }
+
+    fn unmoved_king_file(&self, color: Color, rank: usize) -> Option<usize> {
+        (0..8).find(|&file| self.board[rank][file] == build_piece(PieceType::King(false), color))
+    }
+
+    fn is_unmoved_rook(&self, color: Color, rank: usize, file: usize) -> bool {
+        self.board[rank][file] == build_piece(PieceType::Rook(false), color)
+    }
+}
+
+// Reads the first fen field. Kings and rooks start out as moved, castling
+// rights are applied afterwards, and pawns count as unmoved on their home rank.
+fn parse_placement(field: &str) -> Result<[[Option<Piece>; 8]; 8], FenError> {
+    let bad_placement = || FenError::BadPlacement(field.to_string());
+    let mut board = [[None; 8]; 8];
+
+    let rows: Vec<&str> = field.split('/').collect();
+    if rows.len() != 8 {
+        return Err(bad_placement());
+    }
+
+    for (rank, row) in rows.iter().enumerate() {
+        let mut file = 0;
+        for c in row.chars() {
+            if let Some(skip) = c.to_digit(10) {
+                if skip == 0 || skip > 8 {
+                    return Err(bad_placement());
+                }
+                file += skip as usize;
+                continue;
+            }
+            if file >= 8 {
+                return Err(bad_placement());
+            }
+            let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+            let home_rank = if color.is_white() { 6 } else { 1 };
+            let piece_type = match c.to_ascii_uppercase() {
+                'P' => PieceType::Pawn(rank != home_rank),
+                'R' => PieceType::Rook(true),
+                'N' => PieceType::Knight,
+                'B' => PieceType::Bishop,
+                'Q' => PieceType::Queen,
+                'K' => PieceType::King(true),
+                _ => return Err(bad_placement()),
+            };
+            board[rank][file] = build_piece(piece_type, color);
+            file += 1;
+        }
+        if file != 8 {
+            return Err(bad_placement());
+        }
+    }
+    Ok(board)
+}
@@ -0,0 +1,406 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::{Board, Clock, Color, Move, MoveError, PgnError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    #[default]
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn win_for(color: Color) -> GameResult {
+        match color {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            GameResult::WhiteWins => Some(Color::White),
+            GameResult::BlackWins => Some(Color::Black),
+            _ => None,
+        }
+    }
+}
+
+// written the way pgn does, 1-0, 0-1, 1/2-1/2 or *
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = PgnError;
+
+    fn from_str(s: &str) -> Result<GameResult, PgnError> {
+        match s.trim() {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" | "½-½" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Ongoing),
+            _ => Err(PgnError::BadResult(s.to_string())),
+        }
+    }
+}
+
+// why a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    Repetition,
+    FiftyMoves,
+    TimeForfeit,
+    Resignation,
+    Agreement,
+    Adjudication,
+    Abandoned,
+}
+
+impl Termination {
+    // the value of the pgn Termination tag, which only knows a few broad reasons
+    pub fn pgn_tag(&self) -> &'static str {
+        match self {
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Adjudication => "adjudication",
+            Termination::Abandoned => "abandoned",
+            _ => "normal",
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::Repetition => "threefold repetition",
+            Termination::FiftyMoves => "fifty move rule",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Resignation => "resignation",
+            Termination::Agreement => "agreement",
+            Termination::Adjudication => "adjudication",
+            Termination::Abandoned => "abandoned",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveRecord {
+    pub mv: Move,
+    pub san: String,
+    pub played_at: Option<SystemTime>,
+    pub clock: Option<Duration>, // what the mover had left after the move
+    pub comment: Option<String>,
+}
+
+// A game from its start position: the moves played, who played them and
+// where, and how it ended. The board is always the start replayed through
+// the moves, so it is only changed through the game.
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    pub tags: Vec<(String, String)>, // any other pgn tags
+    pub clock: Option<Clock>,
+    start: Board,
+    board: Board,
+    moves: Vec<MoveRecord>,
+    keys: Vec<u64>, // polyglot key of the start and of the position after each move
+    result: GameResult,
+    termination: Option<Termination>,
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::from_position(Board::default())
+    }
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::default()
+    }
+
+    pub fn from_position(start: Board) -> Game {
+        let mut game = Game {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+            tags: Vec::new(),
+            clock: None,
+            board: start.clone(),
+            keys: vec![start.polyglot_key()],
+            start,
+            moves: Vec::new(),
+            result: GameResult::Ongoing,
+            termination: None,
+        };
+        game.update_result();
+        game
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn moves(&self) -> &[MoveRecord] {
+        &self.moves
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result != GameResult::Ongoing
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.board.side_to_move()
+    }
+
+    // The move number as it is counted in the game score.
+    pub fn move_number(&self) -> u16 {
+        let plies = self.moves.len() as u16 + self.start.turn as u16;
+        self.start.turn_number + 1 + plies / 2
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        match name {
+            "Event" => Some(&self.event),
+            "Site" => Some(&self.site),
+            "Date" => Some(&self.date),
+            "Round" => Some(&self.round),
+            "White" => Some(&self.white),
+            "Black" => Some(&self.black),
+            _ => self
+                .tags
+                .iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.as_str()),
+        }
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        let field = match name {
+            "Event" => &mut self.event,
+            "Site" => &mut self.site,
+            "Date" => &mut self.date,
+            "Round" => &mut self.round,
+            "White" => &mut self.white,
+            "Black" => &mut self.black,
+            _ => {
+                match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+                    Some((_, old)) => *old = value.to_string(),
+                    None => self.tags.push((name.to_string(), value.to_string())),
+                }
+                return;
+            }
+        };
+        *field = value.to_string();
+    }
+
+    // Plays a legal move, pressing the clock when there is one. A move made
+    // after the flag fell loses on time instead.
+    pub fn play(&mut self, mv: &Move) -> Result<(), MoveError> {
+        let text = self.board.move_to_coordinates(mv);
+        if self.check_flag() || self.is_over() {
+            return Err(MoveError::GameOver(text));
+        }
+        if !self.board.is_legal_move(mv) {
+            return Err(MoveError::Illegal(text));
+        }
+        let san = self.board.move_to_san(mv);
+
+        let mover = self.board.side_to_move();
+        let mut clock_left = None;
+        if let Some(clock) = &mut self.clock {
+            if !clock.press() {
+                self.check_flag();
+                return Err(MoveError::GameOver(text));
+            }
+            clock_left = Some(clock.remaining(mover));
+        }
+
+        self.board.apply_move(mv);
+        self.keys.push(self.board.polyglot_key());
+        self.moves.push(MoveRecord {
+            mv: *mv,
+            san,
+            played_at: Some(SystemTime::now()),
+            clock: clock_left,
+            comment: None,
+        });
+        self.update_result();
+        Ok(())
+    }
+
+    // reads the move as SAN or coordinates, then plays it
+    pub fn play_str(&mut self, text: &str) -> Result<Move, MoveError> {
+        if self.check_flag() || self.is_over() {
+            return Err(MoveError::GameOver(text.to_string()));
+        }
+        let mv = self.board.parse_move(text)?;
+        self.play(&mv)?;
+        Ok(mv)
+    }
+
+    // Takes back the last move and whatever result it brought. The clock is
+    // left as it is.
+    pub fn undo(&mut self) -> Option<MoveRecord> {
+        let record = self.moves.pop()?;
+        self.keys.pop();
+        self.board = self.start.clone();
+        for earlier in &self.moves {
+            self.board.apply_move(&earlier.mv);
+        }
+        self.result = GameResult::Ongoing;
+        self.termination = None;
+        self.update_result();
+        Some(record)
+    }
+
+    // puts a comment on the last move played
+    pub fn comment(&mut self, text: &str) -> bool {
+        match self.moves.last_mut() {
+            Some(record) => {
+                record.comment = Some(text.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn resign(&mut self, color: Color) {
+        self.finish(GameResult::win_for(color.opposite()), Termination::Resignation);
+    }
+
+    pub fn agree_draw(&mut self) {
+        self.finish(GameResult::Draw, Termination::Agreement);
+    }
+
+    // Ends the game however the caller decided, like an arbiter or a match
+    // runner adjudicating. Does nothing once the game is over.
+    pub fn finish(&mut self, result: GameResult, termination: Termination) {
+        if !self.is_over() {
+            self.result = result;
+            self.termination = Some(termination);
+        }
+    }
+
+    // A draw by repetition or the fifty move rule has to be claimed, gives
+    // whether the claim was good.
+    pub fn claim_draw(&mut self) -> bool {
+        let termination = if self.is_threefold_repetition() {
+            Termination::Repetition
+        } else if self.is_fifty_move_draw() {
+            Termination::FiftyMoves
+        } else {
+            return false;
+        };
+        self.finish(GameResult::Draw, termination);
+        self.termination == Some(termination)
+    }
+
+    // The position has come up three times with the same side to move, the
+    // same castling rights and the same en passant captures. Only positions
+    // since the last capture or pawn move can repeat.
+    pub fn is_threefold_repetition(&self) -> bool {
+        let current = self.board.polyglot_key();
+        let since = self.keys.len().saturating_sub(self.board.halfmove_clock as usize + 1);
+        self.keys[since..].iter().filter(|&&key| key == current).count() >= 3
+    }
+
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.board.halfmove_clock >= 100
+    }
+
+    // Ends the game on time if a flag has fallen, giving whether it has. Losing
+    // on time against a side that could never mate is a draw.
+    pub fn check_flag(&mut self) -> bool {
+        let flagged = match self.clock.as_ref().and_then(|clock| clock.flagged()) {
+            Some(color) => color,
+            None => return false,
+        };
+        if !self.is_over() {
+            let result = match &self.clock {
+                Some(clock) => crate::result_with_clock(&self.board, clock),
+                None => GameResult::win_for(flagged.opposite()),
+            };
+            self.finish(result, Termination::TimeForfeit);
+        }
+        true
+    }
+
+    // ends the game when the position itself decides it
+    fn update_result(&mut self) {
+        if self.board.is_checkmate() {
+            let winner = self.board.side_to_move().opposite();
+            self.finish(GameResult::win_for(winner), Termination::Checkmate);
+        } else if self.board.is_stalemate() {
+            self.finish(GameResult::Draw, Termination::Stalemate);
+        } else if self.board.is_insufficient_material() {
+            self.finish(GameResult::Draw, Termination::InsufficientMaterial);
+        }
+    }
+
+    // for the pgn reader, which takes results from the file as they are
+    pub(crate) fn set_result(&mut self, result: GameResult, termination: Option<Termination>) {
+        self.result = result;
+        self.termination = termination;
+    }
+
+    pub(crate) fn set_last_clock(&mut self, left: Duration) {
+        if let Some(record) = self.moves.last_mut() {
+            record.clock = Some(left);
+        }
+    }
+
+    pub(crate) fn set_last_played_at(&mut self, time: SystemTime) {
+        if let Some(record) = self.moves.last_mut() {
+            record.played_at = Some(time);
+        }
+    }
+
+    // for the pgn reader, which fills in the times from the comments after
+    pub(crate) fn push_record(&mut self, record: MoveRecord) {
+        self.board.apply_move(&record.mv);
+        self.keys.push(self.board.polyglot_key());
+        self.moves.push(record);
+    }
+}
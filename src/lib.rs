@@ -1,6 +1,70 @@
-use std::io;
-
-#[derive(Debug, Clone, Copy, PartialEq)]
+use std::hash::{Hash, Hasher};
+
+mod chess960;
+mod clock;
+mod epd;
+mod eval;
+mod fen;
+mod game;
+mod matches;
+mod movegen;
+mod ordering;
+mod pgn;
+mod polyglot;
+mod problem;
+mod render;
+mod san;
+mod search;
+mod see;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sprt;
+mod svg;
+#[cfg(feature = "syzygy")]
+mod syzygy;
+mod text;
+mod timeman;
+mod tt;
+mod tune;
+mod uci;
+mod uci_client;
+mod validate;
+
+pub use chess960::chess960_back_rank;
+pub use clock::{
+    format_time, result_with_clock, Clock, Delay, ManualTime, Period, RealTime, TimeControl, TimeControlError,
+    TimeSource,
+};
+pub use epd::{read_epd, run_suite, Epd, EpdError, SuiteOutcome, SuiteScore};
+pub use eval::{evaluate, evaluate_with, piece_value, EvalParams, EvalParamsError, DEFAULT_EVAL_PARAMS};
+pub use fen::{FenError, STARTING_FEN};
+pub use game::{Game, GameResult, MoveRecord, Termination};
+pub use matches::{
+    play_game, play_match, Adjudication, DrawRule, MatchSettings, Opening, Player, PlayerMove, ResignRule,
+    SearcherPlayer,
+};
+pub use movegen::{perft, Move, MoveError};
+pub use pgn::{read_pgn, read_pgn_games, result_of, write_pgn, PgnError};
+pub use polyglot::{decode_book_move, encode_book_move, BookBuilder, BookEntry, BookSelection, OpeningBook};
+pub use problem::{Solution, SolutionNode, Stipulation};
+pub use render::RenderOptions;
+pub use search::{
+    mate_in, SearchLimits, SearchOptions, SearchResult, Searcher, BENCH_POSITIONS, MATE, TABLEBASE_WIN,
+};
+pub use sprt::{elo_from_ratio, MatchScore, Sprt, SprtStatus};
+pub use svg::SvgOptions;
+#[cfg(feature = "syzygy")]
+pub use syzygy::{open_tablebases, tablebase_pieces, Wdl};
+pub use text::{ParseError, Square};
+pub use timeman::{TimeManager, DEFAULT_MOVE_OVERHEAD};
+pub use tt::DEFAULT_HASH_MB;
+pub use tune::{parse_labelled, read_labelled, TuneError, Tuner};
+pub use uci::Uci;
+pub use uci_client::{EngineOutput, GoLimits, UciEngine, UciError, UciOption};
+pub use validate::{PositionError, PositionProblem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Pawn(bool),
     Rook(bool),
@@ -50,7 +114,8 @@ impl PieceType {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     Black,
     White,
@@ -59,9 +124,17 @@ impl Color {
     fn is_white(&self) -> bool {
         self == &Color::White
     }
+
+    pub fn opposite(&self) -> Color {
+        match self {
+            Color::Black => Color::White,
+            Color::White => Color::Black,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub piece_type: PieceType,
     pub color: Color,
@@ -74,13 +147,38 @@ impl Piece {
             self.piece_type.ret_type_as_char().to_ascii_lowercase()
         }
     }
+
+    fn get_piece_as_unicode(&self) -> char {
+        match (self.color, self.piece_type) {
+            (Color::White, PieceType::King(_)) => '\u{2654}',
+            (Color::White, PieceType::Queen) => '\u{2655}',
+            (Color::White, PieceType::Rook(_)) => '\u{2656}',
+            (Color::White, PieceType::Bishop) => '\u{2657}',
+            (Color::White, PieceType::Knight) => '\u{2658}',
+            (Color::White, PieceType::Pawn(_)) => '\u{2659}',
+            (Color::Black, PieceType::King(_)) => '\u{265A}',
+            (Color::Black, PieceType::Queen) => '\u{265B}',
+            (Color::Black, PieceType::Rook(_)) => '\u{265C}',
+            (Color::Black, PieceType::Bishop) => '\u{265D}',
+            (Color::Black, PieceType::Knight) => '\u{265E}',
+            (Color::Black, PieceType::Pawn(_)) => '\u{265F}',
+        }
+    }
 }
 
+// A position with the counters FEN carries alongside it. turn_number is game
+// state, but it stays here rather than moving into Game: it was a public field
+// before Game existed, and a FEN (and the serde and EPD forms built on it) has
+// to round-trip a fullmove number without a Game around it. Game only reads
+// the number of its start board and counts its own moves from there.
+#[derive(Debug, Clone)]
 pub struct Board {
     pub board: [[Option<Piece>; 8]; 8],
-    pub turn: i8,
-    pub turn_number: i8,
+    pub turn: i8, // 0 is white to move, 1 is black
+    pub turn_number: u16, // full moves played, fen shows this plus one
     pub can_en_pessant: [(i32, i32); 2],
+    pub en_pessant_square: Option<(usize, usize)>, // square behind a pawn that just moved two
+    pub halfmove_clock: u16, // moves since the last capture or pawn move
 }
 
 impl Default for Board {
@@ -90,35 +188,130 @@ impl Default for Board {
             turn: 0,
             turn_number: 0,
             can_en_pessant: [(-1, -1), (-1, -1)],
+            en_pessant_square: None,
+            halfmove_clock: 0,
         }
     }
 }
+// Two boards are equal when the same moves are available from them, so the
+// move counters are left out of equality and hashing.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.turn == other.turn
+            && self.can_en_pessant == other.can_en_pessant
+            && self.en_pessant_square == other.en_pessant_square
+    }
+}
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.board.hash(state);
+        self.turn.hash(state);
+        self.can_en_pessant.hash(state);
+        self.en_pessant_square.hash(state);
+    }
+}
+
 impl Board {
     pub fn print_board(&self) {
-        let mut row_num: i8 = 8;
-        println!("  ---------------------------------");
-        for row in &self.board {
-            print!("{} |", row_num);
-            row_num -= 1;
-            for piece in row {
-                match piece {
-                    Some(_) => print!(" {} |", piece.as_ref().unwrap().get_piece_as_char()),
-                    None => print!("   |"),
-                }
-            }
-            println!("");
-            println!("  ---------------------------------");
-        }
-        println!("    A   B   C   D   E   F   G   H  ");
+        print!("{}", self.render(&RenderOptions::default()));
     }
 
     pub fn build_starting_board(&mut self) {
         self.board = build_starting_board();
     }
 
+    pub fn side_to_move(&self) -> Color {
+        if self.turn == 0 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    pub fn king_position(&self, color: Color) -> Option<(usize, usize)> {
+        (0..64)
+            .map(|i| (i / 8, i % 8))
+            .find(|&(rank, file)| {
+                matches!(self.board[rank][file], Some(Piece { piece_type: PieceType::King(_), color: c }) if c == color)
+            })
+    }
+
+    pub fn is_in_check(&self, color: Color) -> bool {
+        match self.king_position(color) {
+            Some(king) => self.is_square_attacked(king, color.opposite()),
+            None => false,
+        }
+    }
+
+    // looks outward from the square for anything of the given color that could capture on it
+    pub fn is_square_attacked(&self, square: (usize, usize), by: Color) -> bool {
+        !self.find_attackers(square, by, true).is_empty()
+    }
+
+    // every square holding a piece of the given color that attacks the square
+    pub fn attackers(&self, square: (usize, usize), by: Color) -> Vec<(usize, usize)> {
+        self.find_attackers(square, by, false)
+    }
+
+    fn find_attackers(&self, square: (usize, usize), by: Color, first_only: bool) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let is_piece = |pos: (usize, usize), wanted: PieceTest| {
+            self.board[pos.0][pos.1].is_some_and(|piece| piece.color == by && wanted(piece.piece_type))
+        };
+
+        // white pawns capture towards rank 0 of the array, so they sit below the square
+        let pawn_rank = if by.is_white() { 1 } else { -1 };
+        let leapers: [(&[(i32, i32)], PieceTest); 3] = [
+            (&[(pawn_rank, -1), (pawn_rank, 1)], |t| matches!(t, PieceType::Pawn(_))),
+            (&KNIGHT_OFFSETS, |t| t == PieceType::Knight),
+            (&KING_OFFSETS, |t| matches!(t, PieceType::King(_))),
+        ];
+        for (steps, wanted) in leapers {
+            for &(rank, file) in steps {
+                if let Some(pos) = offset(square, rank, file) {
+                    if is_piece(pos, wanted) {
+                        found.push(pos);
+                        if first_only {
+                            return found;
+                        }
+                    }
+                }
+            }
+        }
+
+        for (rank, file) in KING_OFFSETS {
+            let diagonal = rank != 0 && file != 0;
+            let mut pos = offset(square, rank, file);
+            while let Some((r, f)) = pos {
+                if let Some(piece) = self.board[r][f] {
+                    let slides_here = match piece.piece_type {
+                        PieceType::Queen => true,
+                        PieceType::Bishop => diagonal,
+                        PieceType::Rook(_) => !diagonal,
+                        _ => false,
+                    };
+                    if piece.color == by && slides_here {
+                        found.push((r, f));
+                        if first_only {
+                            return found;
+                        }
+                    }
+                    break;
+                }
+                pos = offset((r, f), rank, file);
+            }
+        }
+        found
+    }
+
     pub fn print_piece(&self, pos: &str) {
-        let (rank, file) = chess_notation_to_array_notation(pos);
-        println!("{:?}", self.board[rank][file].unwrap());
+        match parse_square(pos).and_then(|(rank, file)| self.board[rank][file]) {
+            Some(piece) => println!("{:?}", piece),
+            None => println!("no piece on {}", pos),
+        }
     }
 
     fn check_en_pessant(&mut self, color: Color, ending_pos: (usize, usize)) {
@@ -161,18 +354,20 @@ impl Board {
 
     fn check_sides(&self, color: Color, rank: usize, to_check: usize) -> (i32, i32) {
         if let Some(side_piece) = self.board[rank][to_check] {
-            if color == side_piece.color {
+            if color == side_piece.color && matches!(side_piece.piece_type, PieceType::Pawn(_)) {
                 return (rank as i32, to_check as i32);
             }
         }
         (-1, -1)
     }
 
+    #[allow(clippy::single_match, clippy::clone_on_copy)]
     fn move_piece(&mut self, beginning_pos: (usize, usize), ending_pos: (usize, usize)) {
         let (beg_rank, beg_file) = beginning_pos;
         let (end_rank, end_file) = ending_pos;
 
         self.can_en_pessant = [(-1,-1),(-1,-1)];
+        self.en_pessant_square = None;
 
         let starting_piece = self.board[beg_rank][beg_file];
 
@@ -185,8 +380,11 @@ impl Board {
                     })
                 },
                 PieceType::Pawn(false) => {
-                    let color = piece.color;
-                    self.check_en_pessant(color, ending_pos);
+                    if beg_rank.abs_diff(end_rank) == 2 {
+                        let color = piece.color;
+                        self.check_en_pessant(color, ending_pos);
+                        self.en_pessant_square = Some(((beg_rank + end_rank) / 2, beg_file));
+                    }
                     self.board[beg_rank][beg_file] = Some(Piece {
                         piece_type: PieceType::Pawn(true),
                         color: piece.color,
@@ -200,85 +398,14 @@ impl Board {
                 },
                 _ => (),
             },
-            None => (),
+            _ => (),
         }
 
-        match starting_piece.unwrap().piece_type {
-            PieceType::Pawn(true) => {
-                match starting_piece.unwrap().color {
-                    Color::Black => {
-                        if end_rank == 7 {
-                            let mut choice = String::new();
-                            println!("Select: N, Q, B, R");
-                            io::stdin().read_line(&mut choice).expect("Failed to read line");
-                            match choice.chars().nth(0).unwrap().to_ascii_uppercase() {
-                                'N' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Knight,
-                                        color: Color::Black,
-                                    })
-                                },
-                                'Q' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Queen,
-                                        color: Color::Black,
-                                    })
-                                },
-                                'B' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Bishop,
-                                        color: Color::Black,
-                                    })
-                                },
-                                'R' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Rook(true),
-                                        color: Color::Black,
-                                    })
-                                },
-                                _ => panic!("Bro idk what to say I'm too lazy to fix this"),
-                            }
-                            println!();
-                        }
-                    },
-                    Color::White => {
-                        if end_rank == 0 {
-                            let mut choice = String::new();
-                            println!("Select: N, Q, B, R");
-                            io::stdin().read_line(&mut choice).expect("Failed to read line");
-                            match choice.chars().nth(0).unwrap().to_ascii_uppercase() {
-                                'N' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Knight,
-                                        color: Color::White,
-                                    })
-                                },
-                                'Q' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Queen,
-                                        color: Color::White,
-                                    })
-                                },
-                                'B' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Bishop,
-                                        color: Color::White,
-                                    })
-                                },
-                                'R' => {
-                                    self.board[beg_rank][beg_file]= Some(Piece {
-                                        piece_type: PieceType::Rook(true),
-                                        color: Color::White,
-                                    })
-                                },
-                                _ => panic!("Bro idk what to say I'm too lazy to fix this"),
-                            }
-                            println!();
-                        }
-                    },
-                }
-            },
-            _ => (),
+        // a pawn reaching the last rank becomes a queen, moves that want another piece swap it afterwards
+        if let Some(Piece { piece_type: PieceType::Pawn(_), color }) = starting_piece {
+            if end_rank == 0 || end_rank == 7 {
+                self.board[beg_rank][beg_file] = build_piece(PieceType::Queen, color);
+            }
         }
 
         self.board[end_rank][end_file] = self.board[beg_rank][beg_file].clone();
@@ -286,10 +413,9 @@ impl Board {
     }
 
     pub fn move_piece_with_chess_notation(&mut self, start: &str, end: &str) {
-        self.move_piece(
-            chess_notation_to_array_notation(start),
-            chess_notation_to_array_notation(end),
-        );
+        if let Some((beginning, ending)) = squares(start, end) {
+            self.move_piece(beginning, ending);
+        }
     }
 
     fn check_legal_move(&self, beginning_pos: (usize, usize), ending_pos: (usize, usize)) -> bool {
@@ -304,12 +430,13 @@ impl Board {
     }
 
     pub fn check_legal_move_chess_notation(&self, beginning_pos: &str, ending_pos: &str) -> bool {
-        self.check_legal_move(
-            chess_notation_to_array_notation(beginning_pos),
-            chess_notation_to_array_notation(ending_pos),
-        )
+        match squares(beginning_pos, ending_pos) {
+            Some((beginning, ending)) => self.check_legal_move(beginning, ending),
+            None => false,
+        }
     }
 
+    #[allow(clippy::redundant_pattern_matching)]
     fn check_collison(&self, beginning_pos: (usize, usize), ending_pos: (usize, usize)) -> bool {
         let board = &self.board;
 
@@ -384,12 +511,13 @@ impl Board {
     }
 
     pub fn check_collison_chess_notation(&self, beginning_pos: &str, ending_pos: &str) -> bool {
-        self.check_collison(
-            chess_notation_to_array_notation(beginning_pos),
-            chess_notation_to_array_notation(ending_pos),
-        )
+        match squares(beginning_pos, ending_pos) {
+            Some((beginning, ending)) => self.check_collison(beginning, ending),
+            None => false,
+        }
     }
 
+    #[allow(clippy::single_match, clippy::collapsible_match)]
     fn make_move(&mut self, beginning_pos: (usize, usize), ending_pos: (usize, usize)) -> bool {
         let board = &mut self.board;
 
@@ -418,127 +546,130 @@ impl Board {
     }
 
     pub fn make_move_chess_notation(&mut self, start: &str, end: &str) -> bool {
-        self.make_move(
-            chess_notation_to_array_notation(start),
-            chess_notation_to_array_notation(end),
-        )
+        match squares(start, end) {
+            Some((beginning, ending)) => self.make_move(beginning, ending),
+            None => false,
+        }
     }
 
+    // Plays the move if it is legal for the side to move. Castling can be given as
+    // the king moving onto its rook or, like over the board, moving two squares.
+    // Promotions made this way are always to a queen.
     fn select_move(&mut self, beginning_pos: (usize, usize), ending_pos: (usize, usize)) -> bool {
-        let (beg_rank, beg_file) = beginning_pos;
-        let (end_rank, end_file) = ending_pos;
-
-        let diff_file: i32 = (end_file as i32 - beg_file as i32).abs();
-        let diff_rank: i32 = end_rank as i32 - beg_rank as i32;
-
-        let starting_piece = self.board[beg_rank][beg_file].unwrap();
-        let ending_piece = self.board[end_rank][end_file];
-
-        // check checking needs to be implemented for castling
-        if let PieceType::King(false) = starting_piece.piece_type {
-            if ending_piece.is_some() {
-                if let PieceType::Rook(false) = ending_piece.unwrap().piece_type {
-                    if !self.check_collison(ending_pos, beginning_pos) {
-                        if end_file > beg_file {
-                            self.move_piece(beginning_pos, (beg_rank, beg_file + 2));
-                            self.move_piece(ending_pos, (beg_rank, beg_file + 1));
-                            return true;
-                        } else {
-                            self.move_piece(beginning_pos, (beg_rank, beg_file - 2));
-                            self.move_piece(ending_pos, (beg_rank, beg_file - 1));
-                            return true;
-                        }
-                    }
-                }
+        let legal_moves = self.legal_moves();
+        let promotes_to_queen = |mv: &&Move| matches!(mv.promotion, None | Some(PieceType::Queen));
+        let chosen = legal_moves
+            .iter()
+            .filter(promotes_to_queen)
+            .find(|mv| mv.from == beginning_pos && mv.to == ending_pos)
+            .or_else(|| {
+                legal_moves.iter().find(|mv| {
+                    mv.from == beginning_pos && self.castling_king_target(mv) == Some(ending_pos)
+                })
+            });
+        match chosen {
+            Some(&mv) => {
+                self.apply_move(&mv);
+                true
             }
+            None => false,
         }
+    }
 
-        // en pessant
-        if let PieceType::Pawn(true) = starting_piece.piece_type {
-            match starting_piece.color {
-                Color::Black => {
-                    if ((beg_rank < end_rank) && (end_rank - beg_rank) == 1) && (diff_file == 1) && ending_piece.is_none() {
-                        for (i,j) in self.can_en_pessant {
-                            if (i == beg_rank as i32) && (j == beg_file as i32) {
-                                self.board[beg_rank][end_file] = None;
-                                self.move_piece(beginning_pos, ending_pos);
-                                return true;
-                            }
-                        }
-                    }
-                }, 
-                Color::White => {
-                    if ((end_rank < beg_rank) && (beg_rank - end_rank) == 1) && (diff_file == 1) && ending_piece.is_none() {
-                        for (i,j) in self.can_en_pessant {
-                            if (i == beg_rank as i32) && (j == beg_file as i32) {
-                                self.board[beg_rank][end_file] = None;
-                                self.move_piece(beginning_pos, ending_pos);
-                                return true;
-                            }
-                        }
-                    }
-                },
-            }
+    // The king always lands on the g or c file and the rook next to it on the f
+    // or d file, wherever they started, so this covers chess960 as well.
+    fn castling_targets(king_pos: (usize, usize), rook_pos: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+        let rank = king_pos.0;
+        if rook_pos.1 > king_pos.1 {
+            ((rank, 6), (rank, 5))
+        } else {
+            ((rank, 2), (rank, 3))
         }
+    }
 
-        // pawn everything else
-        if let PieceType::Pawn(_) = starting_piece.piece_type {
-            if diff_file == 0 {
-                return self.make_move(beginning_pos, ending_pos);
-            } else if diff_file == 1 {
-                match starting_piece.color {
-                    Color::Black => {
-                        if diff_rank == 1 {
-                            match ending_piece {
-                                Some(end_piece) => match end_piece.color {
-                                    Color::Black => {
-                                        return false;
-                                    }
-                                    Color::White => {
-                                        self.move_piece(beginning_pos, ending_pos);
-                                        return true;
-                                    }
-                                },
-                                None => return false,
-                            }
-                        }
-                        return false;
-                    }
-                    Color::White => {
-                        if diff_rank == -1 {
-                            match ending_piece {
-                                Some(end_piece) => match end_piece.color {
-                                    Color::White => {
-                                        return false;
-                                    }
-                                    Color::Black => {
-                                        self.move_piece(beginning_pos, ending_pos);
-                                        return true;
-                                    }
-                                },
-                                None => return false,
-                            }
-                        }
-                        return false;
-                    }
-                }
-            }
+    fn can_castle(&self, king_pos: (usize, usize), rook_pos: (usize, usize)) -> bool {
+        let (king, rook) = match (self.board[king_pos.0][king_pos.1], self.board[rook_pos.0][rook_pos.1]) {
+            (Some(king), Some(rook)) => (king, rook),
+            _ => return false,
+        };
+        if king.piece_type != PieceType::King(false)
+            || rook.piece_type != PieceType::Rook(false)
+            || king.color != rook.color
+            || king_pos.0 != rook_pos.0
+        {
             return false;
         }
 
+        let rank = king_pos.0;
+        let (king_target, rook_target) = Board::castling_targets(king_pos, rook_pos);
 
-        
-        self.make_move(beginning_pos, ending_pos)
+        // everything the king and rook cross or land on has to be empty apart from themselves
+        let files = [king_pos.1, rook_pos.1, king_target.1, rook_target.1];
+        let (low, high) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+        for file in low..=high {
+            if file != king_pos.1 && file != rook_pos.1 && self.board[rank][file].is_some() {
+                return false;
+            }
+        }
+
+        // and the king may not start in, pass through or land in check
+        let (low, high) = (king_pos.1.min(king_target.1), king_pos.1.max(king_target.1));
+        let mut without_rook = self.clone();
+        without_rook.board[rank][rook_pos.1] = None;
+        (low..=high).all(|file| !without_rook.is_square_attacked((rank, file), king.color.opposite()))
+    }
+
+    fn castle(&mut self, king_pos: (usize, usize), rook_pos: (usize, usize)) {
+        let color = self.board[king_pos.0][king_pos.1].unwrap().color;
+        let (king_target, rook_target) = Board::castling_targets(king_pos, rook_pos);
+
+        self.can_en_pessant = [(-1, -1), (-1, -1)];
+        self.en_pessant_square = None;
+        self.board[king_pos.0][king_pos.1] = None;
+        self.board[rook_pos.0][rook_pos.1] = None;
+        self.board[king_target.0][king_target.1] = build_piece(PieceType::King(true), color);
+        self.board[rook_target.0][rook_target.1] = build_piece(PieceType::Rook(true), color);
     }
 
     pub fn select_move_chess_notation(&mut self, start: &str, end: &str) -> bool {
-        self.select_move(
-            chess_notation_to_array_notation(start),
-            chess_notation_to_array_notation(end),
-        )
+        match squares(start, end) {
+            Some((beginning, ending)) => self.select_move(beginning, ending),
+            None => false,
+        }
+    }
+
+    // plays the move on a copy and leaves self untouched, an illegal move gives back an unchanged copy
+    pub fn make_move_copy(&self, beginning_pos: (usize, usize), ending_pos: (usize, usize)) -> Board {
+        let mut copy = self.clone();
+        copy.select_move(beginning_pos, ending_pos);
+        copy
+    }
+
+    pub fn make_move_copy_chess_notation(&self, start: &str, end: &str) -> Board {
+        match squares(start, end) {
+            Some((beginning, ending)) => self.make_move_copy(beginning, ending),
+            None => self.clone(),
+        }
+    }
+}
+
+type PieceTest = fn(PieceType) -> bool;
+
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)];
+const KING_OFFSETS: [(i32, i32); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+// steps from a square, giving None when that walks off the board
+fn offset(pos: (usize, usize), rank: i32, file: i32) -> Option<(usize, usize)> {
+    let new_rank = pos.0 as i32 + rank;
+    let new_file = pos.1 as i32 + file;
+    if (0..8).contains(&new_rank) && (0..8).contains(&new_file) {
+        Some((new_rank as usize, new_file as usize))
+    } else {
+        None
     }
 }
 
+#[allow(clippy::redundant_field_names, clippy::needless_return)]
 fn build_piece(piece: PieceType, color: Color) -> Option<Piece> {
     let to_build = Piece {
         piece_type: piece,
@@ -547,6 +678,7 @@ fn build_piece(piece: PieceType, color: Color) -> Option<Piece> {
     let ret: Option<Piece> = Some(to_build);
     return ret;
 }
+#[allow(clippy::needless_range_loop)]
 fn build_starting_board() -> [[Option<Piece>; 8]; 8] {
     let mut board = [
         [None, None, None, None, None, None, None, None],
@@ -589,13 +721,36 @@ fn build_starting_board() -> [[Option<Piece>; 8]; 8] {
     board
 }
 
+// squares off the board are clamped onto its edge, use parse_square to find out about them
 pub fn chess_notation_to_array_notation(chess_not: &str) -> (usize, usize) /* file is columns*/ {
-    let file: usize = chess_not.chars().nth(0).unwrap().to_ascii_uppercase() as usize - 65;
-    let rank: usize = 7 - (chess_not.chars().nth(1).unwrap() as usize - 49);
+    let mut chars = chess_not.chars();
+    let file = chars.next().map_or(0, |c| (c.to_ascii_uppercase() as usize).saturating_sub(65).min(7));
+    let rank = 7 - chars.next().map_or(0, |c| (c as usize).saturating_sub(49).min(7));
 
     (rank, file)
 }
 
+// both squares of a move, when they are both on the board
+fn squares(start: &str, end: &str) -> Option<((usize, usize), (usize, usize))> {
+    Some((parse_square(start.trim())?, parse_square(end.trim())?))
+}
+
+// like chess_notation_to_array_notation but gives None for anything off the board
+pub fn parse_square(square: &str) -> Option<(usize, usize)> {
+    let mut chars = square.chars();
+    let file = chars.next()?.to_ascii_lowercase();
+    let rank = chars.next()?;
+    if chars.next().is_some() || !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+        return None;
+    }
+    Some((7 - (rank as usize - '1' as usize), file as usize - 'a' as usize))
+}
+
+pub fn array_notation_to_chess_notation(pos: (usize, usize)) -> String {
+    let (rank, file) = pos;
+    format!("{}{}", (b'a' + file as u8) as char, 8 - rank)
+}
+
 // takes 2 ints and returns range between them from the first to the second, regardless of which is larger (excl..excl)
 pub fn ret_range(first: usize, second: usize) -> Box<dyn Iterator<Item = i32>> {
     if first > second {
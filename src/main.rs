@@ -1,56 +1,589 @@
 use rust_chess::*;
+use std::env;
 use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: rust_chess [options]
+  --fen <fen>          start from this position
+  --resume <file>      play on from the end of a saved game; it is only read, add
+                       --autosave with the same file to keep saving into it
+  --autosave <file>    save the game to this pgn file after every move, off unless given
+  --clock <control>    play with clocks, like 300, 180+2 or 40/5400:1800
+  --book <file>        open a Polyglot opening book";
+
+const HELP: &str = "Commands:
+  <move>            play a move, as SAN (Nf3, exd5, O-O, e8=Q) or coordinates (g1f3, e7e8q)
+  undo              take back the last move
+  new               start a new game
+  fen               print the position as fen
+  load <fen>        play on from a fen position
+  moves             list the legal moves
+  flip              turn the board around
+  pgn               print the game so far as pgn
+  save <file>       write the game to a pgn file
+  autosave [<file>|off]  show, move or turn off the file saved after every move
+  load-pgn <file>   play on from the end of the first game in a pgn file
+  setup             edit the position by hand
+  comment <text>    comment on the last move
+  tag <name> <value>  set a game tag, like tag White Magnus or tag Event Club match
+  resign            the side to move gives up
+  draw              both sides agree to a draw
+  claim             claim a draw by threefold repetition or the fifty move rule
+  clock [<control>] show the clocks, or start new ones: 300 is five minutes, 180+2
+                    adds 2 seconds a move, 40/5400:1800 is 40 moves in 90 minutes
+                    then 30 more, d5 and b5 add a simple or Bronstein delay
+  book [<file>]     list the opening book's moves here, or open a Polyglot book
+  book play         play a move from the book, picked by weight
+  go [<seconds>]    let the computer move, from the book while it has one, thinking
+                    3 seconds unless told otherwise
+  solve <problem>   solve the position as a composed problem: #3 is mate in 3, h#2
+                    a helpmate in 2 and s#2 a selfmate in 2
+  help              show this list
+  quit              leave (0 works too)";
+
+#[cfg(feature = "syzygy")]
+const TABLEBASE_HELP: &str = "  tb [<dir>]        look the position up in the endgame tables, or open the Syzygy
+                    tables in a directory";
+
+// The game being played and how it is shown.
+struct Session {
+    game: Game,
+    flipped: bool,
+    autosave: Option<PathBuf>,
+    book: Option<OpeningBook>,
+}
+
+impl Session {
+    fn new(start: Board) -> Session {
+        Session {
+            game: Game::from_position(start),
+            flipped: false,
+            autosave: None,
+            book: None,
+        }
+    }
+
+    // a new game from the board, keeping the orientation, players and time control
+    fn restart(&self, board: Board) -> Session {
+        self.resume(Game::from_position(board))
+    }
+
+    fn resume(&self, game: Game) -> Session {
+        let mut session = Session {
+            game,
+            flipped: self.flipped,
+            autosave: self.autosave.clone(),
+            book: self.book.clone(),
+        };
+        if session.game.white == "?" && session.game.black == "?" {
+            session.game.white = self.game.white.clone();
+            session.game.black = self.game.black.clone();
+        }
+        // a saved game brings its own clock, stopped where it was left
+        let side_to_move = session.game.side_to_move();
+        match (&mut session.game.clock, &self.game.clock) {
+            (Some(clock), _) => clock.start(side_to_move),
+            (None, Some(clock)) => session.start_clock(clock.control().clone()),
+            (None, None) => (),
+        }
+        session
+    }
+
+    fn start_clock(&mut self, control: TimeControl) {
+        let mut clock = Clock::new(control);
+        clock.start(self.game.side_to_move());
+        self.game.clock = Some(clock);
+    }
+
+    fn show(&mut self) {
+        self.game.check_flag();
+        let board = self.game.board();
+        let options = RenderOptions {
+            flipped: self.flipped,
+            last_move: self.game.moves().last().map(|record| (record.mv.from, record.mv.to)),
+            highlight_check: true,
+            ..Default::default()
+        };
+        print!("{}", board.render(&options));
+        if let Some(clock) = &self.game.clock {
+            println!("{}", clock);
+        }
+
+        match self.game.termination() {
+            Some(termination) => println!("Game over by {}, {}", termination, self.game.result()),
+            None if board.is_in_check(board.side_to_move()) => println!("{} is in check", board.side_to_move()),
+            None => (),
+        }
+    }
+
+    fn play(&mut self, text: &str) -> Result<(), String> {
+        match self.game.play_str(text) {
+            Ok(_) => {
+                let record = self.game.moves().last().expect("a move was just played");
+                println!("{}", record.san);
+                Ok(())
+            }
+            // running out of time still shows the board with the result
+            Err(MoveError::GameOver(_)) if self.game.termination() == Some(Termination::TimeForfeit) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn autosave(&self) {
+        if let Some(path) = &self.autosave {
+            if let Err(e) = self.game.save(path) {
+                println!("Could not autosave to {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn book_moves(&self) -> Result<Vec<(Move, u16)>, String> {
+        match &self.book {
+            Some(book) => Ok(book.moves(self.game.board())),
+            None => Err("no book is open, open one with book <file>".to_string()),
+        }
+    }
+
+    // a book move when there is one, otherwise whatever the search finds in the time
+    fn computer_move(&mut self, time: Duration) -> Result<(), String> {
+        if self.game.is_over() {
+            return Err("the game is already over".to_string());
+        }
+        let board = self.game.board();
+        if let Some(mv) = self.book.as_ref().and_then(|book| book.choose_move(board, BookSelection::Weighted)) {
+            let text = board.move_to_coordinates(&mv);
+            println!("From the book");
+            return self.play(&text);
+        }
+
+        let result = Searcher::new().search_game(&self.game, SearchLimits::time(time));
+        let mv = result.best_move.ok_or("there is no move to make")?;
+        let score = match result.mate_in() {
+            Some(moves) => format!("mate in {}", moves),
+            None => format!("{:+.2}", f64::from(result.score) / 100.0),
+        };
+        println!("Depth {}, {} nodes, {}", result.depth, result.nodes, score);
+        let text = board.move_to_coordinates(&mv);
+        self.play(&text)
+    }
+
+    fn undo(&mut self) -> Result<(), String> {
+        match self.game.undo() {
+            Some(_) => Ok(()),
+            None => Err("no moves to take back".to_string()),
+        }
+    }
+
+    // ends the game some way other than on the board
+    fn finish(&mut self, end: impl FnOnce(&mut Game)) -> Result<(), String> {
+        if self.game.is_over() {
+            return Err("the game is already over".to_string());
+        }
+        end(&mut self.game);
+        Ok(())
+    }
+}
 
 fn main() {
     let mut board = Board { ..Default::default()};
-    board.print_board();
-
-    'main_loop: loop {
-        let mut start_move = String::new();
-        let mut end_move = String::new();
-        
-        println!("Input start: ");
-        io::stdin()
-            .read_line(&mut start_move)
-            .expect("Failed to read line");
-        println!("");
-
-        println!("Input end: ");
-        io::stdin()
-            .read_line(&mut end_move)
-            .expect("Failed to read line");
-        println!("");
-        
-        if start_move == "0".to_string() {
-            break 'main_loop;
-        }
-        println!("{}",board.select_move_chess_notation(&start_move, &end_move));
-        board.print_board();
-    }
-
-    // //Rook
-    // println!("True: {}",board.check_collison_chess_notation("d3", "d8"));
-    // println!("False: {}",board.check_collison_chess_notation("d3", "d7"));
-    // println!("False: {}",board.check_collison_chess_notation("d3", "d6"));
-    // //Pawn
-    // println!("True: {}",board.check_collison_chess_notation("d2", "d3"));
-    // println!("True: {}",board.check_collison_chess_notation("d2", "d4"));
-    // println!("False: {}",board.check_collison_chess_notation("c2", "c3"));
-    // //Queen
-    // println!("False: {}",board.check_collison_chess_notation("c4", "c2"));
-    // println!("True: {}",board.check_collison_chess_notation("c4", "c1"));
-    // println!("True: {}",board.check_collison_chess_notation("c4", "e2"));
-    // println!("False: {}",board.check_collison_chess_notation("c4", "d3"));
-    // println!("False: {}",board.check_collison_chess_notation("c4", "c3"));
-    // println!("False: {}",board.check_collison_chess_notation("c4", "b5"));
-    // //King
-    // println!("True: {}",board.check_collison_chess_notation("e1", "d2"));
-    // println!("False: {}",board.check_collison_chess_notation("e1", "e2"));
-    // //Knight
-    // println!("True: {}",board.check_collison_chess_notation("b1", "d2"));
-    // println!("False: {}",board.check_collison_chess_notation("b1", "a3"));
 
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        #[cfg(feature = "syzygy")]
+        println!("  --syzygy <dir>       open the Syzygy endgame tables in a directory");
+        return;
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--fen") {
+        let fen = match args.get(i + 1) {
+            Some(fen) => fen,
+            None => {
+                eprintln!("--fen needs a position");
+                process::exit(1);
+            }
+        };
+        board = match Board::from_fen(fen) {
+            Ok(board) => board,
+            Err(e) => {
+                eprintln!("Could not read fen: {}", e);
+                process::exit(1);
+            }
+        };
+    }
+    let mut session = Session::new(board);
+    if let Some(i) = args.iter().position(|arg| arg == "--autosave") {
+        match args.get(i + 1) {
+            Some(file) => session.autosave = Some(PathBuf::from(file)),
+            None => {
+                eprintln!("--autosave needs a file");
+                process::exit(1);
+            }
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--resume") {
+        let file = match args.get(i + 1) {
+            Some(file) => file,
+            None => {
+                eprintln!("--resume needs a saved game");
+                process::exit(1);
+            }
+        };
+        match Game::load(file) {
+            Ok(game) => session = session.resume(game),
+            Err(e) => {
+                eprintln!("Could not resume {}: {}", file, e);
+                process::exit(1);
+            }
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--clock") {
+        match args.get(i + 1).map(|control| control.parse::<TimeControl>()) {
+            Some(Ok(control)) => session.start_clock(control),
+            Some(Err(e)) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+            None => {
+                eprintln!("--clock needs a time control");
+                process::exit(1);
+            }
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--book") {
+        match args.get(i + 1).map(OpeningBook::open) {
+            Some(Ok(book)) => session.book = Some(book),
+            Some(Err(e)) => {
+                eprintln!("Could not open the book: {}", e);
+                process::exit(1);
+            }
+            None => {
+                eprintln!("--book needs a Polyglot book file");
+                process::exit(1);
+            }
+        }
+    }
+    #[cfg(feature = "syzygy")]
+    if let Some(i) = args.iter().position(|arg| arg == "--syzygy") {
+        match args.get(i + 1).map(open_tablebases) {
+            Some(Ok(count)) => println!("Opened {} endgame tables", count),
+            Some(Err(e)) => {
+                eprintln!("Could not open the endgame tables: {}", e);
+                process::exit(1);
+            }
+            None => {
+                eprintln!("--syzygy needs the directory the tables are in");
+                process::exit(1);
+            }
+        }
+    }
+    session.show();
+    if let Some(path) = &session.autosave {
+        println!("Saving the game to {} after every move", path.display());
+    }
+    println!("Type help for the list of commands");
+
+    loop {
+        let mut line = String::new();
+        println!("{} to move> ", session.game.side_to_move());
+        if io::stdin().read_line(&mut line).expect("Failed to read line") == 0 {
+            break;
+        }
+        let line = line.trim();
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+
+        let result: Result<(), String> = match (command, argument) {
+            ("", _) => continue,
+            ("quit" | "exit" | "0", "") => break,
+            ("help", "") => {
+                println!("{}", HELP);
+                #[cfg(feature = "syzygy")]
+                println!("{}", TABLEBASE_HELP);
+                continue;
+            }
+            ("undo", "") => session.undo(),
+            ("new", "") => {
+                session = session.restart(Board::default());
+                Ok(())
+            }
+            ("fen", "") => {
+                println!("{}", session.game.board().to_fen());
+                continue;
+            }
+            ("load", fen) if !fen.is_empty() => Board::from_fen(fen)
+                .map(|board| session = session.restart(board))
+                .map_err(|e| e.to_string()),
+            ("moves", "") => {
+                let board = session.game.board();
+                let mut moves: Vec<String> = board.legal_moves().iter().map(|mv| board.move_to_san(mv)).collect();
+                moves.sort();
+                println!("{}", moves.join(" "));
+                continue;
+            }
+            ("flip", "") => {
+                session.flipped = !session.flipped;
+                Ok(())
+            }
+            ("pgn", "") => {
+                print!("{}", session.game.to_pgn());
+                continue;
+            }
+            ("save", file) if !file.is_empty() => {
+                match session.game.save(file) {
+                    Ok(()) => println!("Saved to {}", file),
+                    Err(e) => println!("Could not write {}: {}", file, e),
+                }
+                continue;
+            }
+            ("load-pgn", file) if !file.is_empty() => Game::load(file)
+                .map(|game| session = session.resume(game))
+                .map_err(|e| format!("Could not load {}: {}", file, e)),
+            ("autosave", "") => {
+                match &session.autosave {
+                    Some(path) => println!("Saving to {} after every move", path.display()),
+                    None => println!("Autosave is off"),
+                }
+                continue;
+            }
+            ("autosave", "off") => {
+                session.autosave = None;
+                continue;
+            }
+            ("autosave", file) => {
+                session.autosave = Some(PathBuf::from(file));
+                Ok(())
+            }
+            ("setup", "") => {
+                let mut board = session.game.board().clone();
+                setup(&mut board);
+                if &board != session.game.board() {
+                    session = session.restart(board);
+                }
+                Ok(())
+            }
+            ("comment", text) if !text.is_empty() => {
+                if session.game.comment(text) {
+                    session.autosave();
+                    continue;
+                }
+                Err("there is no move to comment on yet".to_string())
+            }
+            ("tag", tag) => match tag.split_once(char::is_whitespace) {
+                Some((name, value)) => {
+                    session.game.set_tag(name, value.trim());
+                    session.autosave();
+                    continue;
+                }
+                None => Err("tag needs a name and a value".to_string()),
+            },
+            ("resign", "") => {
+                let color = session.game.side_to_move();
+                session.finish(|game| game.resign(color))
+            }
+            ("draw", "") => session.finish(Game::agree_draw),
+            ("claim", "") => session.finish(|game| {
+                if !game.claim_draw() {
+                    println!("There is no draw to claim");
+                }
+            }),
+            ("book", "") => session.book_moves().map(|moves| {
+                let board = session.game.board();
+                let total: u32 = moves.iter().map(|&(_, weight)| u32::from(weight)).sum();
+                if moves.is_empty() {
+                    println!("The book has nothing for this position");
+                }
+                for (mv, weight) in moves {
+                    let share = if total == 0 { 0.0 } else { 100.0 * f64::from(weight) / f64::from(total) };
+                    println!("{:8} {:6} {:5.1}%", board.move_to_san(&mv), weight, share);
+                }
+            }),
+            ("book", "play") => {
+                let board = session.game.board();
+                let choice = session
+                    .book
+                    .as_ref()
+                    .and_then(|book| book.choose_move(board, BookSelection::Weighted));
+                match choice {
+                    Some(mv) => {
+                        let text = board.move_to_coordinates(&mv);
+                        session.play(&text)
+                    }
+                    None => session.book_moves().and(Err("the book has no move here".to_string())),
+                }
+            }
+            ("book", file) => OpeningBook::open(file)
+                .map(|book| {
+                    println!("Opened {} with {} entries", file, book.len());
+                    session.book = Some(book);
+                })
+                .map_err(|e| format!("Could not open {}: {}", file, e)),
+            ("go", "") => session.computer_move(Duration::from_secs(3)),
+            ("go", seconds) => match seconds.parse::<f64>() {
+                Ok(seconds) if seconds > 0.0 && seconds.is_finite() => {
+                    session.computer_move(Duration::from_secs_f64(seconds))
+                }
+                _ => Err(format!("{} is not a number of seconds", seconds)),
+            },
+            ("solve", problem) if !problem.is_empty() => {
+                match parse_problem(problem) {
+                    Some((stipulation, moves)) => {
+                        let board = session.game.board();
+                        match board.solve(stipulation, moves) {
+                            Some(solution) => print!("{}", solution.render(board)),
+                            None => println!("There is no {}{} here", stipulation, moves),
+                        }
+                    }
+                    None => println!("{} is not a problem, try #2, h#3 or s#2", problem),
+                }
+                continue;
+            }
+            #[cfg(feature = "syzygy")]
+            ("tb", "") => {
+                let board = session.game.board();
+                match (board.probe_wdl(), board.probe_dtz()) {
+                    (Some(wdl), Some(dtz)) => println!("Tables say {} for {}, dtz {}", wdl, board.side_to_move(), dtz),
+                    (Some(wdl), None) => println!("Tables say {} for {}", wdl, board.side_to_move()),
+                    _ => println!("No table covers this position"),
+                }
+                continue;
+            }
+            #[cfg(feature = "syzygy")]
+            ("tb", dir) => match open_tablebases(dir) {
+                Ok(count) => {
+                    println!("Opened {} endgame tables, up to {} pieces", count, tablebase_pieces());
+                    continue;
+                }
+                Err(e) => Err(format!("Could not open tables in {}: {}", dir, e)),
+            },
+            ("clock", "") => match &session.game.clock {
+                Some(clock) => {
+                    println!("{}", clock);
+                    continue;
+                }
+                None => Err("no clock is running, start one with clock <control>".to_string()),
+            },
+            ("clock", control) => control
+                .parse::<TimeControl>()
+                .map(|control| session.start_clock(control))
+                .map_err(|e| e.to_string()),
+            _ => session.play(line),
+        };
+
+        match result {
+            Ok(()) => {
+                session.show();
+                session.autosave();
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+// #3, h#2 or s#2
+fn parse_problem(text: &str) -> Option<(Stipulation, u32)> {
+    let (stipulation, moves) = text.split_once('#')?;
+    let stipulation = match stipulation.to_ascii_lowercase().as_str() {
+        "" => Stipulation::Mate,
+        "h" => Stipulation::Helpmate,
+        "s" => Stipulation::Selfmate,
+        _ => return None,
+    };
+    Some((stipulation, moves.trim().parse().ok().filter(|&moves| moves > 0)?))
 }
 
+const SETUP_HELP: &str = "Setup commands:
+  put <piece> <square>  place a piece, uppercase is white (put K e1, put p e7)
+  remove <square>       empty a square
+  clear                 empty the whole board
+  start                 the normal starting position
+  turn <w|b>            side to move
+  castling <KQkq|->     castling rights, files like HAha work for chess960
+  ep <square|->         en passant square
+  show                  print the board and its fen
+  done                  check the position and play from it
+  cancel                go back to the old position";
 
+// Edits a copy of the board, only handing it back once it is playable.
+fn setup(board: &mut Board) {
+    let mut edited = board.clone();
+    println!("{}", SETUP_HELP);
 
+    loop {
+        let mut line = String::new();
+        println!("setup> ");
+        if io::stdin().read_line(&mut line).expect("Failed to read line") == 0 {
+            return;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let result: Result<(), String> = match words.as_slice() {
+            ["put", piece, square] => match (piece.parse::<Piece>(), square.parse::<Square>()) {
+                (Ok(piece), Ok(square)) => {
+                    edited.board[square.rank][square.file] = Some(placed_piece(piece, square));
+                    Ok(())
+                }
+                (Err(e), _) | (_, Err(e)) => Err(e.to_string()),
+            },
+            ["remove", square] => square
+                .parse::<Square>()
+                .map(|square| edited.board[square.rank][square.file] = None)
+                .map_err(|e| e.to_string()),
+            ["clear"] => {
+                edited.board = [[None; 8]; 8];
+                edited.set_en_pessant_square(None).map_err(|e| e.to_string())
+            }
+            ["start"] => {
+                edited = Board::default();
+                Ok(())
+            }
+            ["turn", color] => match color.parse::<Color>() {
+                Ok(color) => {
+                    edited.turn = if color == Color::White { 0 } else { 1 };
+                    edited.set_en_pessant_square(None).map_err(|e| e.to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            },
+            ["castling", rights] => edited.set_castling_rights(rights).map_err(|e| e.to_string()),
+            ["ep", "-"] => edited.set_en_pessant_square(None).map_err(|e| e.to_string()),
+            ["ep", square] => match square.parse::<Square>() {
+                Ok(square) => edited.set_en_pessant_square(Some(square.into())).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+            ["show"] => {
+                edited.print_board();
+                println!("{}", edited.to_fen());
+                Ok(())
+            }
+            ["done"] => match edited.validate() {
+                Ok(()) => {
+                    *board = edited;
+                    return;
+                }
+                Err(e) => Err(e.to_string()),
+            },
+            ["cancel"] => return,
+            _ => Err(SETUP_HELP.to_string()),
+        };
+
+        if let Err(e) = result {
+            println!("{}", e);
+        }
+    }
+}
+
+// Pawns on their home rank can still move two, everything else counts as moved
+// until castling rights say otherwise.
+fn placed_piece(piece: Piece, square: Square) -> Piece {
+    let home_rank = if piece.color == Color::White { 6 } else { 1 };
+    let piece_type = match piece.piece_type {
+        PieceType::Pawn(_) => PieceType::Pawn(square.rank != home_rank),
+        PieceType::King(_) => PieceType::King(true),
+        PieceType::Rook(_) => PieceType::Rook(true),
+        other => other,
+    };
+    Piece { piece_type, color: piece.color }
+}
@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use crate::{
+    Board, Clock, Color, Game, GameResult, MatchScore, Move, MoveError, SearchLimits, Searcher, Sprt, SprtStatus,
+    Termination, TimeControl, TimeManager, DEFAULT_MOVE_OVERHEAD,
+};
+
+// What a player answers with: its move, and the score it gives the position
+// in centipawns for the side that moved if it has one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerMove {
+    pub mv: Move,
+    pub score: Option<i32>,
+}
+
+// Something that can play a side in a match, this crate's search or another
+// engine.
+pub trait Player {
+    fn name(&self) -> &str;
+
+    // called before each game, to forget what belongs to the last one
+    fn new_game(&mut self);
+
+    // The move for the side to move. The game's clock, when it has one, is
+    // running for that side. An error loses the game.
+    fn play(&mut self, game: &Game) -> Result<PlayerMove, String>;
+}
+
+// This crate's search as a player, on the game's clock when there is one
+// and otherwise to a fixed depth or number of nodes.
+#[derive(Debug)]
+pub struct SearcherPlayer {
+    name: String,
+    pub searcher: Searcher,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub move_overhead: Duration,
+}
+
+impl SearcherPlayer {
+    pub fn new(name: &str, searcher: Searcher) -> SearcherPlayer {
+        SearcherPlayer {
+            name: name.to_string(),
+            searcher,
+            depth: None,
+            nodes: None,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+        }
+    }
+}
+
+impl Player for SearcherPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self) {
+        self.searcher.new_game();
+    }
+
+    fn play(&mut self, game: &Game) -> Result<PlayerMove, String> {
+        let side = game.side_to_move();
+        let limits = SearchLimits {
+            depth: self.depth,
+            nodes: self.nodes,
+            time: None,
+            time_manager: game.clock.as_ref().map(|clock| {
+                TimeManager::new(
+                    clock.remaining(side),
+                    clock.increment(side),
+                    clock.moves_to_go(side),
+                    self.move_overhead,
+                )
+            }),
+        };
+        let result = self.searcher.search_game(game, limits);
+        match result.best_move {
+            Some(mv) => Ok(PlayerMove { mv, score: Some(result.score) }),
+            None => Err("no move to play".to_string()),
+        }
+    }
+}
+
+// Where the games of a match start: a position and the moves already made
+// from it, which go into the game score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    pub start: Board,
+    pub moves: Vec<Move>,
+}
+
+impl Opening {
+    pub fn new(start: Board) -> Opening {
+        Opening { start, moves: Vec::new() }
+    }
+
+    // the moves of a game, from wherever it starts
+    pub fn from_game(game: &Game) -> Opening {
+        Opening {
+            start: game.start().clone(),
+            moves: game.moves().iter().map(|record| record.mv).collect(),
+        }
+    }
+
+    pub fn game(&self) -> Result<Game, MoveError> {
+        let mut game = Game::from_position(self.start.clone());
+        for mv in &self.moves {
+            game.play(mv)?;
+        }
+        Ok(game)
+    }
+}
+
+// When a game is ended before the rules end it. Scores are the ones the
+// players give their own moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Adjudication {
+    // a draw once both sides have scored within score of equal for the
+    // last moves moves each, from move number after on
+    pub draw: Option<DrawRule>,
+    // a win once both sides have agreed for the last moves moves each that
+    // one of them is at least score ahead
+    pub resign: Option<ResignRule>,
+    // a draw after this many moves each from the end of the opening
+    pub max_moves: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DrawRule {
+    pub after: u16,
+    pub moves: u32,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResignRule {
+    pub moves: u32,
+    pub score: i32,
+}
+
+impl Adjudication {
+    // The result to give the game now, if any. scores has what the players
+    // gave each move since the opening, the last move last.
+    pub fn decide(&self, game: &Game, scores: &[Option<i32>]) -> Option<GameResult> {
+        // white's point of view, for the last plies moves
+        let last = |plies: usize| -> Option<Vec<i32>> {
+            if plies == 0 || scores.len() < plies {
+                return None;
+            }
+            let mover_is_white = |back: usize| (game.side_to_move() == Color::Black) == back.is_multiple_of(2);
+            scores[scores.len() - plies..]
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(back, score)| score.map(|score| if mover_is_white(back) { score } else { -score }))
+                .collect()
+        };
+
+        if let Some(rule) = self.resign {
+            if let Some(scores) = last(rule.moves as usize * 2) {
+                if scores.iter().all(|&score| score >= rule.score) {
+                    return Some(GameResult::WhiteWins);
+                }
+                if scores.iter().all(|&score| score <= -rule.score) {
+                    return Some(GameResult::BlackWins);
+                }
+            }
+        }
+        if let Some(rule) = self.draw {
+            if game.move_number() > rule.after {
+                if let Some(scores) = last(rule.moves as usize * 2) {
+                    if scores.iter().all(|&score| score.abs() <= rule.score) {
+                        return Some(GameResult::Draw);
+                    }
+                }
+            }
+        }
+        if let Some(moves) = self.max_moves {
+            if scores.len() >= moves as usize * 2 {
+                return Some(GameResult::Draw);
+            }
+        }
+        None
+    }
+}
+
+// How a match is played. Every opening is played twice, once with each
+// player as white, and the openings are used in turn.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MatchSettings {
+    pub games: u32,
+    pub time_control: Option<TimeControl>,
+    pub adjudication: Adjudication,
+    pub sprt: Option<Sprt>, // stops the match once the test is decided
+    pub event: String,
+}
+
+// Plays one game between the two from the opening. A player that cannot
+// move or answers with an illegal move loses.
+pub fn play_game(
+    white: &mut dyn Player,
+    black: &mut dyn Player,
+    opening: &Opening,
+    time_control: Option<&TimeControl>,
+    adjudication: &Adjudication,
+) -> Result<Game, MoveError> {
+    let mut game = opening.game()?;
+    game.white = white.name().to_string();
+    game.black = black.name().to_string();
+    white.new_game();
+    black.new_game();
+    if let Some(control) = time_control {
+        let mut clock = Clock::new(control.clone());
+        clock.start(game.side_to_move());
+        game.clock = Some(clock);
+    }
+
+    let mut scores = Vec::new();
+    while !game.is_over() && !game.claim_draw() {
+        if let Some(result) = adjudication.decide(&game, &scores) {
+            game.finish(result, Termination::Adjudication);
+            break;
+        }
+        let side = game.side_to_move();
+        let player: &mut dyn Player = if side == Color::White { white } else { black };
+        let forfeit = match player.play(&game) {
+            Ok(reply) => match game.play(&reply.mv) {
+                Ok(()) => {
+                    scores.push(reply.score);
+                    None
+                }
+                // the flag fell while it thought, the game has already
+                // been given to the other side
+                Err(MoveError::GameOver(_)) => None,
+                Err(e) => Some(e.to_string()),
+            },
+            Err(e) => Some(e),
+        };
+        if let Some(reason) = forfeit {
+            game.set_tag("TerminationDetails", &format!("{} {}", side, reason));
+            game.finish(GameResult::win_for(side.opposite()), Termination::Abandoned);
+        }
+    }
+    Ok(game)
+}
+
+// Plays the match, telling on_game about each game as it ends along with
+// the score so far. The score is from the first player's point of view.
+pub fn play_match(
+    first: &mut dyn Player,
+    second: &mut dyn Player,
+    openings: &[Opening],
+    settings: &MatchSettings,
+    mut on_game: impl FnMut(&Game, &MatchScore),
+) -> Result<MatchScore, MoveError> {
+    let mut score = MatchScore::default();
+    let start = [Opening::new(Board::default())];
+    let openings = if openings.is_empty() { &start[..] } else { openings };
+    for round in 0..settings.games {
+        let opening = &openings[(round / 2) as usize % openings.len()];
+        let first_is_white = round % 2 == 0;
+        let (white, black): (&mut dyn Player, &mut dyn Player) = if first_is_white {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let mut game = play_game(
+            white,
+            black,
+            opening,
+            settings.time_control.as_ref(),
+            &settings.adjudication,
+        )?;
+        game.event = settings.event.clone();
+        game.round = (round + 1).to_string();
+
+        let first_color = if first_is_white { Color::White } else { Color::Black };
+        match game.result().winner() {
+            Some(color) if color == first_color => score.wins += 1,
+            Some(_) => score.losses += 1,
+            None => score.draws += 1,
+        }
+        on_game(&game, &score);
+        // only once both colors have had the opening, so the test does not
+        // stop on a score that favours whoever had white
+        if round % 2 == 1 && settings.sprt.is_some_and(|sprt| sprt.status(&score) != SprtStatus::Continue) {
+            break;
+        }
+    }
+    Ok(score)
+}
@@ -0,0 +1,338 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{
+    array_notation_to_chess_notation, build_piece, offset, parse_square, Board, Color, Piece,
+    PieceType, KING_OFFSETS, KNIGHT_OFFSETS,
+};
+
+// Castling is stored as the king moving onto its own rook, the same way
+// select_move takes it, which works for chess960 as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    pub promotion: Option<PieceType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    Unreadable(String),
+    Illegal(String),
+    Ambiguous(String),
+    GameOver(String),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::Unreadable(text) => write!(f, "could not read move {:?}", text),
+            MoveError::Illegal(text) => write!(f, "{} is not a legal move here", text),
+            MoveError::Ambiguous(text) => write!(f, "{} could mean more than one move", text),
+            MoveError::GameOver(text) => write!(f, "cannot play {}, the game is over", text),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+const PROMOTIONS: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook(true),
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+impl Board {
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let color = self.side_to_move();
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                let mut after = self.clone();
+                after.apply_move(mv);
+                !after.is_in_check(color)
+            })
+            .collect()
+    }
+
+    pub fn is_legal_move(&self, mv: &Move) -> bool {
+        self.legal_moves().contains(mv)
+    }
+
+    // Moves that follow how the pieces move without caring whether they leave
+    // the king in check. Castling is only offered when it is fully legal.
+    pub(crate) fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let color = self.side_to_move();
+        let mut moves = Vec::new();
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let piece = match self.board[rank][file] {
+                    Some(piece) if piece.color == color => piece,
+                    _ => continue,
+                };
+                let from = (rank, file);
+                match piece.piece_type {
+                    PieceType::Pawn(moved) => self.pawn_moves(from, color, moved, &mut moves),
+                    PieceType::Knight => self.step_moves(from, color, &KNIGHT_OFFSETS, &mut moves),
+                    PieceType::King(moved) => {
+                        self.step_moves(from, color, &KING_OFFSETS, &mut moves);
+                        if !moved {
+                            for rook_file in 0..8 {
+                                if self.can_castle(from, (rank, rook_file)) {
+                                    moves.push(Move { from, to: (rank, rook_file), promotion: None });
+                                }
+                            }
+                        }
+                    }
+                    PieceType::Bishop => self.slide_moves(from, color, true, false, &mut moves),
+                    PieceType::Rook(_) => self.slide_moves(from, color, false, true, &mut moves),
+                    PieceType::Queen => self.slide_moves(from, color, true, true, &mut moves),
+                }
+            }
+        }
+        moves
+    }
+
+    fn pawn_moves(&self, from: (usize, usize), color: Color, moved: bool, moves: &mut Vec<Move>) {
+        let forward = if color.is_white() { -1 } else { 1 };
+        let last_rank = if color.is_white() { 0 } else { 7 };
+        let mut push = |to: (usize, usize)| {
+            if to.0 == last_rank {
+                for promotion in PROMOTIONS {
+                    moves.push(Move { from, to, promotion: Some(promotion) });
+                }
+            } else {
+                moves.push(Move { from, to, promotion: None });
+            }
+        };
+
+        if let Some(one) = offset(from, forward, 0) {
+            if self.board[one.0][one.1].is_none() {
+                push(one);
+                if !moved {
+                    if let Some(two) = offset(from, 2 * forward, 0) {
+                        if self.board[two.0][two.1].is_none() {
+                            push(two);
+                        }
+                    }
+                }
+            }
+        }
+        for side in [-1, 1] {
+            if let Some(to) = offset(from, forward, side) {
+                let takes_piece = self.board[to.0][to.1].is_some_and(|piece| piece.color != color);
+                if takes_piece || self.en_pessant_square == Some(to) {
+                    push(to);
+                }
+            }
+        }
+    }
+
+    fn step_moves(&self, from: (usize, usize), color: Color, steps: &[(i32, i32)], moves: &mut Vec<Move>) {
+        for &(rank, file) in steps {
+            if let Some(to) = offset(from, rank, file) {
+                if self.board[to.0][to.1].is_none_or(|piece| piece.color != color) {
+                    moves.push(Move { from, to, promotion: None });
+                }
+            }
+        }
+    }
+
+    fn slide_moves(&self, from: (usize, usize), color: Color, diagonals: bool, straights: bool, moves: &mut Vec<Move>) {
+        for (rank, file) in KING_OFFSETS {
+            let diagonal = rank != 0 && file != 0;
+            if (diagonal && !diagonals) || (!diagonal && !straights) {
+                continue;
+            }
+            let mut to = offset(from, rank, file);
+            while let Some(pos) = to {
+                match self.board[pos.0][pos.1] {
+                    None => moves.push(Move { from, to: pos, promotion: None }),
+                    Some(piece) => {
+                        if piece.color != color {
+                            moves.push(Move { from, to: pos, promotion: None });
+                        }
+                        break;
+                    }
+                }
+                to = offset(pos, rank, file);
+            }
+        }
+    }
+
+    pub fn is_castling(&self, mv: &Move) -> bool {
+        match (self.board[mv.from.0][mv.from.1], self.board[mv.to.0][mv.to.1]) {
+            (Some(king), Some(rook)) => {
+                matches!(king.piece_type, PieceType::King(_))
+                    && matches!(rook.piece_type, PieceType::Rook(_))
+                    && king.color == rook.color
+            }
+            _ => false,
+        }
+    }
+
+    // where the king ends up when the move is castling
+    pub(crate) fn castling_king_target(&self, mv: &Move) -> Option<(usize, usize)> {
+        if self.is_castling(mv) {
+            Some(Board::castling_targets(mv.from, mv.to).0)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_en_pessant(&self, mv: &Move) -> bool {
+        matches!(self.board[mv.from.0][mv.from.1], Some(Piece { piece_type: PieceType::Pawn(_), .. }))
+            && mv.from.1 != mv.to.1
+            && self.board[mv.to.0][mv.to.1].is_none()
+    }
+
+    pub fn is_capture(&self, mv: &Move) -> bool {
+        self.is_en_pessant(mv) || (!self.is_castling(mv) && self.board[mv.to.0][mv.to.1].is_some())
+    }
+
+    // Plays the move if it is legal, leaving the board alone otherwise.
+    pub fn play_move(&mut self, mv: &Move) -> bool {
+        if !self.is_legal_move(mv) {
+            return false;
+        }
+        self.apply_move(mv);
+        true
+    }
+
+    // Plays a move without checking it, the move has to come from the move generator.
+    pub(crate) fn apply_move(&mut self, mv: &Move) {
+        let piece = match self.board[mv.from.0][mv.from.1] {
+            Some(piece) => piece,
+            None => return,
+        };
+        let resets_clock = self.is_capture(mv) || matches!(piece.piece_type, PieceType::Pawn(_));
+
+        if self.is_castling(mv) {
+            self.castle(mv.from, mv.to);
+        } else {
+            if self.is_en_pessant(mv) {
+                self.board[mv.from.0][mv.to.1] = None;
+            }
+            self.move_piece(mv.from, mv.to);
+            if let Some(promotion) = mv.promotion {
+                self.board[mv.to.0][mv.to.1] = build_piece(promotion, piece.color);
+            }
+        }
+
+        if resets_clock {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.turn == 1 {
+            self.turn_number = self.turn_number.saturating_add(1);
+        }
+        self.turn = 1 - self.turn;
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_in_check(self.side_to_move()) && self.legal_moves().is_empty()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check(self.side_to_move()) && self.legal_moves().is_empty()
+    }
+
+    // Neither side can mate with what is left: bare kings, a single minor
+    // piece, or only bishops that all stand on the same color of square.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = Vec::new();
+        for rank in 0..8 {
+            for file in 0..8 {
+                match self.board[rank][file].map(|piece| piece.piece_type) {
+                    None | Some(PieceType::King(_)) => (),
+                    Some(PieceType::Knight) => minors.push(None),
+                    Some(PieceType::Bishop) => minors.push(Some((rank + file) % 2)),
+                    Some(_) => return false,
+                }
+            }
+        }
+        match minors.as_slice() {
+            [] | [_] => true,
+            [first, rest @ ..] => first.is_some() && rest.iter().all(|square| square == first),
+        }
+    }
+
+    // Long algebraic notation as UCI uses it, e2e4 or e7e8q. Castling is shown
+    // as the king moving two squares in normal chess and onto the rook in chess960.
+    pub fn move_to_coordinates(&self, mv: &Move) -> String {
+        let mut to = mv.to;
+        if self.is_castling(mv) && self.is_standard_castling(mv) {
+            to = Board::castling_targets(mv.from, mv.to).0;
+        }
+        let mut text = format!(
+            "{}{}",
+            array_notation_to_chess_notation(mv.from),
+            array_notation_to_chess_notation(to)
+        );
+        if let Some(promotion) = mv.promotion {
+            text.push(promotion.ret_type_as_char().to_ascii_lowercase());
+        }
+        text
+    }
+
+    // Reads e2e4, e7e8q or with a space or dash between the squares. Castling
+    // is accepted both as the king's two square step and as king takes rook.
+    pub fn parse_coordinates(&self, text: &str) -> Result<Move, MoveError> {
+        let unreadable = || MoveError::Unreadable(text.to_string());
+        let cleaned: String = text.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+        if cleaned.len() < 4 || cleaned.len() > 5 || !cleaned.is_ascii() {
+            return Err(unreadable());
+        }
+        let from = parse_square(&cleaned[0..2]).ok_or_else(unreadable)?;
+        let to = parse_square(&cleaned[2..4]).ok_or_else(unreadable)?;
+        let promotion = match cleaned[4..].to_ascii_lowercase().as_str() {
+            "" => None,
+            "q" => Some(PieceType::Queen),
+            "r" => Some(PieceType::Rook(true)),
+            "b" => Some(PieceType::Bishop),
+            "n" => Some(PieceType::Knight),
+            _ => return Err(unreadable()),
+        };
+
+        let legal_moves = self.legal_moves();
+        legal_moves
+            .iter()
+            .find(|mv| mv.from == from && mv.to == to && mv.promotion == promotion)
+            .or_else(|| {
+                legal_moves.iter().find(|mv| {
+                    mv.from == from && mv.promotion.is_none() && self.castling_king_target(mv) == Some(to)
+                })
+            })
+            .copied()
+            .ok_or_else(|| MoveError::Illegal(text.to_string()))
+    }
+
+    // king from the e file with a rook from the a or h file, as in a normal game
+    fn is_standard_castling(&self, mv: &Move) -> bool {
+        mv.from.1 == 4 && (mv.to.1 == 0 || mv.to.1 == 7)
+    }
+}
+
+// Counts the leaf nodes of the move tree, the usual check on a move generator.
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .iter()
+        .map(|mv| {
+            let mut after = board.clone();
+            after.apply_move(mv);
+            perft(&after, depth - 1)
+        })
+        .sum()
+}
+
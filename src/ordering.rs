@@ -0,0 +1,149 @@
+use crate::eval::piece_value;
+use crate::search::{SearchOptions, MAX_PLY};
+use crate::{Board, Color, Move, PieceType};
+
+// Where each kind of move goes in the list, best first. History scores are
+// kept below the countermove so they never pass it.
+const HASH_MOVE: i32 = 1_000_000;
+const GOOD_CAPTURE: i32 = 200_000;
+const KILLER: i32 = 100_000;
+const COUNTERMOVE: i32 = 90_000;
+const HISTORY_MAX: i32 = 50_000;
+const BAD_CAPTURE: i32 = -200_000;
+
+fn square_index(square: (usize, usize)) -> usize {
+    square.0 * 8 + square.1
+}
+
+// What the search has learned about which quiet moves cause cutoffs: two
+// killers for each ply, a history score for every from and to square, and
+// the move that last refuted each move of the opponent.
+#[derive(Debug, Clone)]
+pub(crate) struct MoveOrderer {
+    killers: Vec<[Option<Move>; 2]>,
+    history: Vec<[[i32; 64]; 64]>,
+    countermoves: Vec<[Option<Move>; 64]>,
+}
+
+impl Default for MoveOrderer {
+    fn default() -> MoveOrderer {
+        MoveOrderer {
+            killers: vec![[None; 2]; MAX_PLY as usize + 1],
+            history: vec![[[0; 64]; 64]; 2],
+            countermoves: vec![[None; 64]; 64],
+        }
+    }
+}
+
+impl MoveOrderer {
+    pub fn clear(&mut self) {
+        *self = MoveOrderer::default();
+    }
+
+    // forgets the killers, which belong to one search, and ages the history
+    pub fn new_search(&mut self) {
+        self.killers.iter_mut().for_each(|killers| *killers = [None; 2]);
+        for table in &mut self.history {
+            table.iter_mut().flatten().for_each(|score| *score /= 8);
+        }
+    }
+
+    // the moves sorted best first, as far as the options turned on can tell
+    pub fn order(
+        &self,
+        board: &Board,
+        moves: Vec<Move>,
+        hash_move: Option<Move>,
+        previous: Option<Move>,
+        ply: u32,
+        options: &SearchOptions,
+    ) -> Vec<Move> {
+        let mut scored: Vec<(i32, Move)> = moves
+            .into_iter()
+            .map(|mv| (self.score(board, &mv, hash_move, previous, ply, options), mv))
+            .collect();
+        // stable, so moves that score the same keep the generator's order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, mv)| mv).collect()
+    }
+
+    fn score(
+        &self,
+        board: &Board,
+        mv: &Move,
+        hash_move: Option<Move>,
+        previous: Option<Move>,
+        ply: u32,
+        options: &SearchOptions,
+    ) -> i32 {
+        if options.hash_move && hash_move == Some(*mv) {
+            return HASH_MOVE;
+        }
+        if board.is_capture(mv) || mv.promotion.is_some() {
+            if !options.capture_ordering {
+                return 0;
+            }
+            let mvv_lva = mvv_lva(board, mv);
+            if !options.see_ordering || board.see(mv) >= 0 {
+                return GOOD_CAPTURE + mvv_lva;
+            }
+            return BAD_CAPTURE + mvv_lva;
+        }
+        if options.killers && self.killers[ply as usize].contains(&Some(*mv)) {
+            return KILLER + if self.killers[ply as usize][0] == Some(*mv) { 1 } else { 0 };
+        }
+        if options.countermoves {
+            if let Some(previous) = previous {
+                if self.countermoves[square_index(previous.from)][square_index(previous.to)] == Some(*mv) {
+                    return COUNTERMOVE;
+                }
+            }
+        }
+        if options.history {
+            return self.history[color_index(board.side_to_move())][square_index(mv.from)][square_index(mv.to)];
+        }
+        0
+    }
+
+    // remembers a quiet move that was too good for the opponent to allow
+    pub fn cutoff(&mut self, board: &Board, mv: &Move, previous: Option<Move>, ply: u32, depth: i32) {
+        let killers = &mut self.killers[ply as usize];
+        if killers[0] != Some(*mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(*mv);
+        }
+        if let Some(previous) = previous {
+            self.countermoves[square_index(previous.from)][square_index(previous.to)] = Some(*mv);
+        }
+
+        let history = &mut self.history[color_index(board.side_to_move())];
+        history[square_index(mv.from)][square_index(mv.to)] += depth * depth;
+        if history[square_index(mv.from)][square_index(mv.to)] > HISTORY_MAX {
+            history.iter_mut().flatten().for_each(|score| *score /= 2);
+        }
+    }
+}
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+// Most valuable victim, least valuable attacker: queen takes pawn comes after
+// pawn takes queen. Promotions count the piece they make.
+pub(crate) fn mvv_lva(board: &Board, mv: &Move) -> i32 {
+    let victim = match board.board[mv.to.0][mv.to.1] {
+        Some(piece) => piece_value(piece.piece_type),
+        None if board.is_en_pessant(mv) => piece_value(PieceType::Pawn(true)),
+        None => 0,
+    };
+    let attacker = match board.board[mv.from.0][mv.from.1] {
+        Some(piece) if matches!(piece.piece_type, PieceType::King(_)) => 1000,
+        Some(piece) => piece_value(piece.piece_type),
+        None => 0,
+    };
+    let promotion = mv.promotion.map_or(0, piece_value);
+    (victim + promotion) * 10 - attacker / 10
+}
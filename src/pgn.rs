@@ -0,0 +1,447 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Board, Clock, Color, FenError, Game, GameResult, MoveError, MoveRecord, Termination, TimeControl};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    BadTag(String),
+    BadFen(FenError),
+    BadResult(String),
+    BadMove { ply: usize, error: MoveError },
+    UnmatchedParenthesis(char), // a variation closed that was never opened, or left open
+    NoGame,
+    Io(String),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::BadTag(line) => write!(f, "could not read tag {:?}", line),
+            PgnError::BadFen(e) => write!(f, "bad FEN tag: {}", e),
+            PgnError::BadResult(text) => write!(f, "{:?} is not a game result", text),
+            PgnError::BadMove { ply, error } => write!(f, "move {}: {}", ply / 2 + 1, error),
+            PgnError::UnmatchedParenthesis(c) => write!(f, "unmatched {:?} in the moves", c),
+            PgnError::NoGame => write!(f, "no game found"),
+            PgnError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+// The result a finished position calls for, or Ongoing while the game goes on.
+pub fn result_of(board: &Board) -> GameResult {
+    if board.is_checkmate() {
+        GameResult::win_for(board.side_to_move().opposite())
+    } else if board.is_stalemate() || board.is_insufficient_material() {
+        GameResult::Draw
+    } else {
+        GameResult::Ongoing
+    }
+}
+
+impl Game {
+    pub fn to_pgn(&self) -> String {
+        write_pgn(self)
+    }
+
+    pub fn from_pgn(text: &str) -> Result<Game, PgnError> {
+        read_pgn(text)
+    }
+
+    // Writes the game as pgn, with when each move was played as a %ts command
+    // next to its clock, going through a temporary file so a crash while
+    // saving never leaves half a game behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, write_game(self, true))?;
+        fs::rename(&temporary, path)
+    }
+
+    // reads back a game written by save, clocks and move times included
+    pub fn load(path: impl AsRef<Path>) -> Result<Game, PgnError> {
+        let text = fs::read_to_string(path).map_err(|e| PgnError::Io(e.to_string()))?;
+        read_pgn(&text)
+    }
+}
+
+// Writes the seven standard tags first, then the rest, then the moves with
+// their comments and clock times wrapped at 80 columns.
+pub fn write_pgn(game: &Game) -> String {
+    write_game(game, false)
+}
+
+fn write_game(game: &Game, timestamps: bool) -> String {
+    let mut pgn = String::new();
+    for name in ["Event", "Site", "Date", "Round", "White", "Black"] {
+        pgn.push_str(&format_tag(name, game.tag(name).unwrap_or("?")));
+    }
+    pgn.push_str(&format_tag("Result", &game.result().to_string()));
+    let start_fen = game.start().to_fen();
+    if start_fen != crate::STARTING_FEN {
+        pgn.push_str(&format_tag("SetUp", "1"));
+        pgn.push_str(&format_tag("FEN", &start_fen));
+    }
+    if let Some(clock) = &game.clock {
+        if !game.tags.iter().any(|(name, _)| name == "TimeControl") {
+            pgn.push_str(&format_tag("TimeControl", &clock.control().to_string()));
+        }
+    }
+    if let Some(termination) = game.termination() {
+        if !game.tags.iter().any(|(name, _)| name == "Termination") {
+            pgn.push_str(&format_tag("Termination", termination.pgn_tag()));
+        }
+    }
+    for (name, value) in &game.tags {
+        if !matches!(name.as_str(), "Result" | "SetUp" | "FEN") {
+            pgn.push_str(&format_tag(name, value));
+        }
+    }
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    let mut number = game.start().turn_number + 1;
+    let mut white_to_move = game.start().turn == 0;
+    let mut after_comment = false;
+    for (i, record) in game.moves().iter().enumerate() {
+        if white_to_move {
+            tokens.push(format!("{}.", number));
+        } else if i == 0 || after_comment {
+            tokens.push(format!("{}...", number));
+        }
+        tokens.push(record.san.clone());
+
+        let mut comment = Vec::new();
+        if let Some(left) = record.clock {
+            comment.push(format!("[%clk {}]", clock_time(left)));
+        }
+        if let Some(time) = record.played_at.filter(|_| timestamps) {
+            comment.push(format!("[%ts {}]", timestamp(time)));
+        }
+        if let Some(text) = &record.comment {
+            comment.push(text.replace('}', ")"));
+        }
+        after_comment = !comment.is_empty();
+        if after_comment {
+            // each word on its own so long comments wrap too
+            let comment = format!("{{{}}}", comment.join(" "));
+            tokens.extend(comment.split_whitespace().map(str::to_string));
+        }
+
+        if !white_to_move {
+            number += 1;
+        }
+        white_to_move = !white_to_move;
+    }
+    tokens.push(game.result().to_string());
+    pgn.push_str(&wrap(&tokens, 80));
+    pgn.push('\n');
+    pgn
+}
+
+// h:mm:ss as the %clk command wants it, with tenths when there are any so a
+// saved game keeps its clocks exactly
+fn clock_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    let tenths = time.subsec_millis() / 100;
+    let text = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    if tenths == 0 {
+        text
+    } else {
+        format!("{}.{}", text, tenths)
+    }
+}
+
+fn parse_clock_time(text: &str) -> Option<Duration> {
+    let mut secs = 0.0;
+    for part in text.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok().filter(|n| n.is_finite() && *n >= 0.0)?;
+    }
+    Some(Duration::from_secs_f64(secs))
+}
+
+// When a move was played, for the %ts command, as seconds since the Unix
+// epoch with as many decimals as it takes to keep it exactly.
+fn timestamp(time: SystemTime) -> String {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let text = format!("{}.{:09}", since.as_secs(), since.subsec_nanos());
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn parse_timestamp(text: &str) -> Option<SystemTime> {
+    let (secs, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = format!("{:0<9}", fraction).parse().ok()?;
+    UNIX_EPOCH.checked_add(Duration::new(secs.parse().ok()?, nanos))
+}
+
+fn format_tag(name: &str, value: &str) -> String {
+    format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn wrap(tokens: &[String], width: usize) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 && line_length + 1 + token.len() > width {
+            text.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            text.push(' ');
+            line_length += 1;
+        }
+        text.push_str(token);
+        line_length += token.len();
+    }
+    text
+}
+
+// Reads the first game of a pgn.
+pub fn read_pgn(text: &str) -> Result<Game, PgnError> {
+    read_pgn_games(text).into_iter().next().unwrap_or(Err(PgnError::NoGame))
+}
+
+enum Token {
+    Move(String),
+    Comment(String),
+}
+
+// Reads every game in a pgn file. Variations and numeric annotation glyphs are
+// skipped over, comments are kept on the move they follow. A game whose
+// parentheses do not pair up is an error rather than a guess at its moves.
+pub fn read_pgn_games(text: &str) -> Vec<Result<Game, PgnError>> {
+    let mut games = Vec::new();
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut finished = false;
+    let mut broken = None;
+
+    let mut chars = text.chars().peekable();
+    let mut variation_depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                if variation_depth == 0 {
+                    tokens.push(Token::Comment(comment));
+                }
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '\n').collect();
+                if variation_depth == 0 {
+                    tokens.push(Token::Comment(comment));
+                }
+            }
+            '(' => variation_depth += 1,
+            ')' if variation_depth == 0 => broken = Some(PgnError::UnmatchedParenthesis(')')),
+            ')' => variation_depth -= 1,
+            '[' if variation_depth == 0 => {
+                // a tag after moves starts the next game
+                if !tokens.is_empty() || finished {
+                    games.push(broken.take().map_or_else(|| build_game(&tags, &tokens), Err));
+                    tags.clear();
+                    tokens.clear();
+                    finished = false;
+                }
+                let mut line = String::new();
+                let mut in_string = false;
+                let mut escaped = false;
+                for c in chars.by_ref() {
+                    if c == ']' && !in_string {
+                        break;
+                    }
+                    if in_string && c == '\\' && !escaped {
+                        escaped = true;
+                        line.push(c);
+                        continue;
+                    }
+                    if c == '"' && !escaped {
+                        in_string = !in_string;
+                    }
+                    escaped = false;
+                    line.push(c);
+                }
+                tags.push(parse_tag(&line));
+            }
+            c if c.is_whitespace() => (),
+            c if variation_depth == 0 => {
+                let mut token = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '{' | '(' | ')' | ';' | '[') {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    if !tags.iter().any(|(name, _)| name == "Result") {
+                        tags.push(("Result".to_string(), token));
+                    }
+                    finished = true;
+                    continue;
+                }
+                // drop move numbers, whether they stand alone or are stuck to the move
+                let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                let token = if token[digits..].starts_with('.') {
+                    token[digits..].trim_start_matches('.')
+                } else {
+                    &token
+                };
+                if !token.is_empty() && !token.starts_with('$') {
+                    tokens.push(Token::Move(token.to_string()));
+                }
+            }
+            _ => (),
+        }
+    }
+    if variation_depth > 0 {
+        broken = Some(PgnError::UnmatchedParenthesis('('));
+    }
+    if !tokens.is_empty() || !tags.is_empty() || broken.is_some() {
+        games.push(broken.map_or_else(|| build_game(&tags, &tokens), Err));
+    }
+    games
+}
+
+fn parse_tag(line: &str) -> (String, String) {
+    let line = line.trim();
+    let (name, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let value = value.trim();
+    let value = value.strip_prefix('"').unwrap_or(value);
+    let value = value.strip_suffix('"').unwrap_or(value);
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    (name.to_string(), unescaped)
+}
+
+fn build_game(tags: &[(String, String)], tokens: &[Token]) -> Result<Game, PgnError> {
+    if let Some((name, _)) = tags.iter().find(|(name, _)| name.is_empty()) {
+        return Err(PgnError::BadTag(name.clone()));
+    }
+    let start = match tags.iter().find(|(name, _)| name == "FEN") {
+        Some((_, fen)) => Board::from_fen(fen).map_err(PgnError::BadFen)?,
+        None => Board::default(),
+    };
+
+    let mut game = Game::from_position(start);
+    let mut result = GameResult::Ongoing;
+    let mut termination = None;
+    for (name, value) in tags {
+        match name.as_str() {
+            "Result" => result = value.parse()?,
+            "SetUp" | "FEN" => (),
+            "Termination" => {
+                termination = match value.as_str() {
+                    "time forfeit" => Some(Termination::TimeForfeit),
+                    "adjudication" => Some(Termination::Adjudication),
+                    "abandoned" => Some(Termination::Abandoned),
+                    _ => None,
+                };
+                game.set_tag(name, value);
+            }
+            _ => game.set_tag(name, value),
+        }
+    }
+
+    let mut ply = 0;
+    for token in tokens {
+        match token {
+            Token::Move(text) => {
+                let mv = game
+                    .board()
+                    .parse_san(text)
+                    .map_err(|error| PgnError::BadMove { ply, error })?;
+                let san = game.board().move_to_san(&mv);
+                game.push_record(MoveRecord {
+                    mv,
+                    san,
+                    played_at: None,
+                    clock: None,
+                    comment: None,
+                });
+                ply += 1;
+            }
+            Token::Comment(text) => game.annotate_last(text),
+        }
+    }
+
+    if let Some(control) = game.tag("TimeControl").and_then(|control| control.parse::<TimeControl>().ok()) {
+        game.clock = Some(restored_clock(&game, control));
+    }
+
+    // the position settles a normal ending, a file can still say more, like a
+    // resignation or a flag
+    let decided = result_of(game.board());
+    if decided != GameResult::Ongoing && decided == result {
+        termination = termination.or(Some(match game.board() {
+            board if board.is_checkmate() => Termination::Checkmate,
+            board if board.is_stalemate() => Termination::Stalemate,
+            _ => Termination::InsufficientMaterial,
+        }));
+    }
+    game.set_result(result, termination);
+    Ok(game)
+}
+
+impl Game {
+    // takes the %clk and %ts commands out of a comment and keeps the rest as the comment
+    fn annotate_last(&mut self, text: &str) {
+        let mut text = text.trim().to_string();
+        if let Some(left) = take_command(&mut text, "%clk").and_then(|value| parse_clock_time(&value)) {
+            self.set_last_clock(left);
+        }
+        if let Some(time) = take_command(&mut text, "%ts").and_then(|value| parse_timestamp(&value)) {
+            self.set_last_played_at(time);
+        }
+        if !text.is_empty() {
+            let joined = match self.moves().last().and_then(|record| record.comment.clone()) {
+                Some(earlier) => format!("{} {}", earlier, text),
+                None => text,
+            };
+            self.comment(&joined);
+        }
+    }
+}
+
+// removes a [%name value] command from the text and gives its value
+fn take_command(text: &mut String, name: &str) -> Option<String> {
+    let opening = format!("[{}", name);
+    let start = text
+        .match_indices(&opening)
+        .map(|(start, _)| start)
+        .find(|&start| text[start + opening.len()..].starts_with(char::is_whitespace))?;
+    let length = text[start..].find(']')?;
+    let value = text[start + name.len() + 1..start + length].trim().to_string();
+    text.replace_range(start..start + length + 1, "");
+    *text = text.trim().to_string();
+    Some(value)
+}
+
+// A clock for a game read back in, set from the last %clk each side wrote.
+fn restored_clock(game: &Game, control: TimeControl) -> Clock {
+    let mut clock = Clock::new(control);
+    let first_mover = game.start().side_to_move();
+    for color in [Color::White, Color::Black] {
+        let offset = if color == first_mover { 0 } else { 1 };
+        let own_moves: Vec<_> = game.moves().iter().skip(offset).step_by(2).collect();
+        if let Some(left) = own_moves.iter().rev().find_map(|record| record.clock) {
+            clock.restore(color, left, own_moves.len() as u32);
+        }
+    }
+    clock
+}
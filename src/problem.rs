@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Board, Move};
+
+// What a composed problem asks for, in the usual notation: # for mate, h#
+// for a helpmate and s# for a selfmate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stipulation {
+    // the side to move mates, whatever the other side does
+    Mate,
+    // both sides work together so that the side to move gets mated
+    Helpmate,
+    // the side to move forces the other side to mate it
+    Selfmate,
+}
+
+impl fmt::Display for Stipulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stipulation::Mate => write!(f, "#"),
+            Stipulation::Helpmate => write!(f, "h#"),
+            Stipulation::Selfmate => write!(f, "s#"),
+        }
+    }
+}
+
+// A move in the solution and what follows it: every reply for the side
+// being forced, and every move that still does it for the side forcing.
+// In a helpmate both sides are only shown the moves that still work.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionNode {
+    pub mv: Move,
+    pub continuations: Vec<SolutionNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub stipulation: Stipulation,
+    pub moves: u32,
+    pub tree: Vec<SolutionNode>, // one node for each key move
+}
+
+impl Solution {
+    pub fn key_moves(&self) -> Vec<Move> {
+        self.tree.iter().map(|node| node.mv).collect()
+    }
+
+    // the tree in SAN, one move to a line and indented by how deep it is,
+    // the key moves marked with !
+    pub fn render(&self, board: &Board) -> String {
+        let mut text = String::new();
+        for node in &self.tree {
+            render_node(board, node, 0, &mut text);
+        }
+        text
+    }
+}
+
+fn render_node(board: &Board, node: &SolutionNode, depth: usize, text: &mut String) {
+    let number = if board.side_to_move().is_white() {
+        format!("{}.", board.turn_number + 1)
+    } else {
+        format!("{}...", board.turn_number + 1)
+    };
+    let key = if depth == 0 { "!" } else { "" };
+    text.push_str(&format!("{}{}{}{}\n", "  ".repeat(depth), number, board.move_to_san(&node.mv), key));
+    let mut after = board.clone();
+    after.apply_move(&node.mv);
+    for continuation in &node.continuations {
+        render_node(&after, continuation, depth + 1, text);
+    }
+}
+
+impl Board {
+    // Proves or disproves that the side to move can meet the stipulation in
+    // at most the given number of its own moves, looking at every line. The
+    // solution has all the key moves, and is None when there are none.
+    // Draws by repetition or the fifty move rule do not count, as in
+    // composed problems.
+    pub fn solve(&self, stipulation: Stipulation, moves: u32) -> Option<Solution> {
+        if moves == 0 {
+            return None;
+        }
+        let mut solver = Solver { stipulation, known: HashMap::new() };
+        let tree = solver.keys(self, moves);
+        if tree.is_empty() {
+            return None;
+        }
+        Some(Solution { stipulation, moves, tree })
+    }
+}
+
+struct Solver {
+    stipulation: Stipulation,
+    known: HashMap<(u64, u32), bool>, // whether a position solves in so many moves
+}
+
+impl Solver {
+    // whether some move of the side to move meets the stipulation in n
+    fn solves(&mut self, board: &Board, n: u32) -> bool {
+        let key = (board.polyglot_key(), n);
+        if let Some(&known) = self.known.get(&key) {
+            return known;
+        }
+        let solves = board.legal_moves().iter().any(|mv| self.works(&after(board, mv), n));
+        self.known.insert(key, solves);
+        solves
+    }
+
+    // whether the move that led to the position meets the stipulation in n,
+    // counting it as the first of them
+    fn works(&mut self, board: &Board, n: u32) -> bool {
+        match self.stipulation {
+            Stipulation::Mate => {
+                if board.is_checkmate() {
+                    return true;
+                }
+                let replies = board.legal_moves();
+                n > 1 && !replies.is_empty() && replies.iter().all(|reply| self.solves(&after(board, reply), n - 1))
+            }
+            Stipulation::Selfmate => {
+                let replies = board.legal_moves();
+                !replies.is_empty() && replies.iter().all(|reply| self.done(&after(board, reply), n))
+            }
+            Stipulation::Helpmate => board.legal_moves().iter().any(|reply| self.done(&after(board, reply), n)),
+        }
+    }
+
+    // after the other side's answer in a helpmate or selfmate: mated, or
+    // still able to get mated in the moves left
+    fn done(&mut self, board: &Board, n: u32) -> bool {
+        board.is_checkmate() || (n > 1 && self.solves(board, n - 1))
+    }
+
+    fn keys(&mut self, board: &Board, n: u32) -> Vec<SolutionNode> {
+        let mut nodes = Vec::new();
+        for mv in board.legal_moves() {
+            let child = after(board, &mv);
+            if self.works(&child, n) {
+                nodes.push(SolutionNode { mv, continuations: self.answers(&child, n) });
+            }
+        }
+        nodes
+    }
+
+    // the other side's moves after a key move and what follows each
+    fn answers(&mut self, board: &Board, n: u32) -> Vec<SolutionNode> {
+        let mut nodes = Vec::new();
+        if self.stipulation == Stipulation::Mate && board.is_checkmate() {
+            return nodes;
+        }
+        for reply in board.legal_moves() {
+            let child = after(board, &reply);
+            if self.stipulation == Stipulation::Helpmate && !self.done(&child, n) {
+                continue;
+            }
+            let continuations = if child.is_checkmate() { Vec::new() } else { self.keys(&child, n - 1) };
+            nodes.push(SolutionNode { mv: reply, continuations });
+        }
+        nodes
+    }
+}
+
+fn after(board: &Board, mv: &Move) -> Board {
+    let mut child = board.clone();
+    child.apply_move(mv);
+    child
+}
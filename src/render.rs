@@ -0,0 +1,131 @@
+use std::io;
+
+use crate::{Board, Color, Piece};
+
+const LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+const DARK_SQUARE: &str = "\x1b[48;5;137m";
+const LAST_MOVE_SQUARE: &str = "\x1b[48;5;143m";
+const CHECK_SQUARE: &str = "\x1b[48;5;160m";
+const WHITE_PIECE: &str = "\x1b[1;97m";
+const BLACK_PIECE: &str = "\x1b[1;30m";
+const RESET: &str = "\x1b[0m";
+
+// The default options draw the plain ascii grid that print_board has always shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub unicode: bool,                // figurines instead of letters
+    pub colors: bool,                 // ansi colored squares instead of the grid
+    pub flipped: bool,                // black at the bottom
+    pub coordinates_both_sides: bool, // files above and ranks to the right as well
+    pub last_move: Option<((usize, usize), (usize, usize))>,
+    pub highlight_check: bool,
+}
+
+impl Board {
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let mut out = Vec::new();
+        self.write_board(&mut out, options)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("the board is always valid utf8")
+    }
+
+    pub fn write_board<W: io::Write>(&self, out: &mut W, options: &RenderOptions) -> io::Result<()> {
+        let checked_king = if options.highlight_check {
+            let color = self.side_to_move();
+            self.king_position(color).filter(|_| self.is_in_check(color))
+        } else {
+            None
+        };
+
+        let ranks: Vec<usize> = if options.flipped {
+            (0..8).rev().collect()
+        } else {
+            (0..8).collect()
+        };
+        let files: Vec<usize> = if options.flipped {
+            (0..8).rev().collect()
+        } else {
+            (0..8).collect()
+        };
+
+        let file_labels: String = if options.colors {
+            files.iter().map(|&file| format!(" {} ", file_letter(file))).collect()
+        } else {
+            files.iter().map(|&file| format!("  {} ", file_letter(file))).collect()
+        };
+        let separator = "  ---------------------------------";
+
+        if options.coordinates_both_sides {
+            writeln!(out, "  {}", file_labels)?;
+        }
+        if !options.colors {
+            writeln!(out, "{}", separator)?;
+        }
+
+        for &rank in &ranks {
+            if options.colors {
+                write!(out, "{} ", 8 - rank)?;
+            } else {
+                write!(out, "{} |", 8 - rank)?;
+            }
+
+            for &file in &files {
+                let piece = self.board[rank][file];
+                let glyph = match piece {
+                    Some(piece) if options.unicode => piece.get_piece_as_unicode(),
+                    Some(piece) => piece.get_piece_as_char(),
+                    None => ' ',
+                };
+                let in_last_move = options
+                    .last_move
+                    .is_some_and(|(start, end)| start == (rank, file) || end == (rank, file));
+                let in_check = checked_king == Some((rank, file));
+
+                if options.colors {
+                    let background = if in_check {
+                        CHECK_SQUARE
+                    } else if in_last_move {
+                        LAST_MOVE_SQUARE
+                    } else if (rank + file) % 2 == 0 {
+                        LIGHT_SQUARE
+                    } else {
+                        DARK_SQUARE
+                    };
+                    let foreground = match piece {
+                        Some(Piece { color: Color::White, .. }) => WHITE_PIECE,
+                        _ => BLACK_PIECE,
+                    };
+                    write!(out, "{}{} {} {}", background, foreground, glyph, RESET)?;
+                } else {
+                    let (left, right) = if in_check {
+                        ('(', ')')
+                    } else if in_last_move {
+                        ('[', ']')
+                    } else {
+                        (' ', ' ')
+                    };
+                    write!(out, "{}{}{}|", left, glyph, right)?;
+                }
+            }
+
+            if options.coordinates_both_sides {
+                write!(out, " {}", 8 - rank)?;
+            }
+            writeln!(out)?;
+            if !options.colors {
+                writeln!(out, "{}", separator)?;
+            }
+        }
+
+        if options.colors {
+            writeln!(out, "  {}", file_labels)?;
+        } else {
+            writeln!(out, "  {} ", file_labels)?;
+        }
+        Ok(())
+    }
+}
+
+fn file_letter(file: usize) -> char {
+    (b'A' + file as u8) as char
+}
@@ -0,0 +1,160 @@
+use crate::{array_notation_to_chess_notation, parse_square, Board, Move, MoveError, PieceType};
+
+impl Board {
+    // Standard algebraic notation for a legal move, like Nbd7, exd6, O-O or e8=Q+.
+    pub fn move_to_san(&self, mv: &Move) -> String {
+        let mut san = String::new();
+        let piece = match self.board[mv.from.0][mv.from.1] {
+            Some(piece) => piece,
+            None => return san,
+        };
+
+        if self.is_castling(mv) {
+            san.push_str(if mv.to.1 > mv.from.1 { "O-O" } else { "O-O-O" });
+        } else {
+            let capture = self.is_capture(mv);
+            match piece.piece_type {
+                PieceType::Pawn(_) => {
+                    if capture {
+                        san.push((b'a' + mv.from.1 as u8) as char);
+                    }
+                }
+                piece_type => {
+                    san.push(piece_type.ret_type_as_char());
+                    // name the starting file, rank or both when another piece of
+                    // the same kind could also go to that square
+                    let others: Vec<Move> = self
+                        .legal_moves()
+                        .into_iter()
+                        .filter(|other| {
+                            other.to == mv.to
+                                && other.from != mv.from
+                                && self.board[other.from.0][other.from.1].map(|p| p.piece_type.ret_type_as_char())
+                                    == Some(piece_type.ret_type_as_char())
+                                && !self.is_castling(other)
+                        })
+                        .collect();
+                    if !others.is_empty() {
+                        let square = array_notation_to_chess_notation(mv.from);
+                        if others.iter().all(|other| other.from.1 != mv.from.1) {
+                            san.push_str(&square[0..1]);
+                        } else if others.iter().all(|other| other.from.0 != mv.from.0) {
+                            san.push_str(&square[1..2]);
+                        } else {
+                            san.push_str(&square);
+                        }
+                    }
+                }
+            }
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&array_notation_to_chess_notation(mv.to));
+            if let Some(promotion) = mv.promotion {
+                san.push('=');
+                san.push(promotion.ret_type_as_char());
+            }
+        }
+
+        let mut after = self.clone();
+        after.apply_move(mv);
+        if after.is_checkmate() {
+            san.push('#');
+        } else if after.is_in_check(after.side_to_move()) {
+            san.push('+');
+        }
+        san
+    }
+
+    // Reads standard algebraic notation. Check marks, annotations and the x for
+    // captures are optional, and 0-0 is taken for O-O.
+    pub fn parse_san(&self, text: &str) -> Result<Move, MoveError> {
+        let unreadable = || MoveError::Unreadable(text.to_string());
+        let cleaned: String = text
+            .trim()
+            .chars()
+            .filter(|c| !matches!(c, '+' | '#' | '!' | '?' | 'x' | ':' | '='))
+            .collect();
+        let legal_moves = self.legal_moves();
+
+        let castling = match cleaned.as_str() {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(king_side) = castling {
+            return legal_moves
+                .into_iter()
+                .find(|mv| self.is_castling(mv) && (mv.to.1 > mv.from.1) == king_side)
+                .ok_or_else(|| MoveError::Illegal(text.to_string()));
+        }
+
+        let mut chars: Vec<char> = cleaned.chars().collect();
+        if !chars.iter().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(unreadable());
+        }
+
+        let piece_letter = match chars.first() {
+            Some(&c) if "NBRQK".contains(c) => {
+                chars.remove(0);
+                c
+            }
+            Some(_) => 'P',
+            None => return Err(unreadable()),
+        };
+
+        let promotion = match chars.last() {
+            Some(&c) if piece_letter == 'P' && "NBRQnrq".contains(c) => {
+                chars.pop();
+                Some(match c.to_ascii_uppercase() {
+                    'N' => PieceType::Knight,
+                    'B' => PieceType::Bishop,
+                    'R' => PieceType::Rook(true),
+                    _ => PieceType::Queen,
+                })
+            }
+            _ => None,
+        };
+
+        if chars.len() < 2 {
+            return Err(unreadable());
+        }
+        let target: String = chars[chars.len() - 2..].iter().collect();
+        let to = parse_square(&target).ok_or_else(unreadable)?;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &c in &chars[..chars.len() - 2] {
+            match c {
+                'a'..='h' => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' => from_rank = Some(7 - (c as usize - '1' as usize)),
+                _ => return Err(unreadable()),
+            }
+        }
+
+        let matching: Vec<Move> = legal_moves
+            .into_iter()
+            .filter(|mv| {
+                mv.to == to
+                    && mv.promotion == promotion
+                    && !self.is_castling(mv)
+                    && self.board[mv.from.0][mv.from.1].map(|p| p.piece_type.ret_type_as_char()) == Some(piece_letter)
+                    && from_file.is_none_or(|file| mv.from.1 == file)
+                    && from_rank.is_none_or(|rank| mv.from.0 == rank)
+            })
+            .collect();
+        match matching.as_slice() {
+            [mv] => Ok(*mv),
+            [] => Err(MoveError::Illegal(text.to_string())),
+            _ => Err(MoveError::Ambiguous(text.to_string())),
+        }
+    }
+
+    // Takes a move in either standard algebraic or coordinate notation.
+    pub fn parse_move(&self, text: &str) -> Result<Move, MoveError> {
+        self.parse_coordinates(text).or_else(|coordinate_error| match self.parse_san(text) {
+            Ok(mv) => Ok(mv),
+            Err(MoveError::Unreadable(_)) => Err(coordinate_error),
+            Err(e) => Err(e),
+        })
+    }
+}
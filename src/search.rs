@@ -0,0 +1,841 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::eval::{evaluate_with, EvalParams};
+use crate::ordering::{mvv_lva, MoveOrderer};
+use crate::tt::{Bound, Entry, TranspositionTable, DEFAULT_HASH_MB};
+use crate::{Board, Game, Move, PieceType, RealTime, TimeManager, TimeSource};
+
+// Scores past these mean a forced mate or a tablebase win, the distance to it
+// taken off so nearer ones score higher.
+pub const MATE: i32 = 30_000;
+pub const TABLEBASE_WIN: i32 = 20_000;
+pub(crate) const MAX_PLY: u32 = 128;
+
+// how often the clock and the stop flag are looked at
+const CHECK_EVERY: u64 = 1024;
+
+// How far below alpha a quiet move may leave the evaluation one and two
+// plies from the leaves before it is not worth searching, and how far above
+// beta the evaluation has to be, per ply of depth, to cut the node.
+const FUTILITY_MARGINS: [i32; 3] = [0, 200, 500];
+const REVERSE_FUTILITY_MARGIN: i32 = 120;
+const ASPIRATION_WINDOW: i32 = 50;
+
+// A fixed set of positions for comparing searches by the nodes they take:
+// the start and a few openings, middlegames and endgames.
+pub const BENCH_POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r2q1rk1/ppp2ppp/2n1bn2/2bpp3/4P3/2PP1N2/PP1NBPPP/R1BQ1RK1 w - - 0 8",
+    "2r3k1/pp3ppp/2n1p3/3pP3/3P4/P1N2N2/1P3PPP/2R3K1 w - - 0 20",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/8/4k3/3p4/3P4/4K3/8/8 w - - 0 1",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+];
+
+// Which of the search's refinements are on. All of them are by default,
+// turning them off one at a time shows what each is worth. The ordering ones
+// are for the main search, the capture search at the leaves always takes the
+// most valuable victim first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub hash_move: bool,        // the table's best move first
+    pub capture_ordering: bool, // then captures, most valuable victim first
+    pub see_ordering: bool,     // captures that lose material after the rest
+    pub killers: bool,          // quiet moves that refuted a sibling
+    pub countermoves: bool,     // the move that last refuted the opponent's move
+    pub history: bool,          // quiet moves by how often they caused cutoffs
+    pub null_move: bool,        // let the opponent move twice, if even that fails to help them the node is cut
+    pub late_move_reductions: bool, // quiet moves late in the list searched shallower
+    pub futility: bool,         // quiet moves near the leaves skipped when far below alpha
+    pub reverse_futility: bool, // nodes near the leaves cut when far above beta
+    pub aspiration: bool,       // each iteration starts with a narrow window round the last score
+    pub check_extensions: bool, // a side in check is searched a ply deeper
+    pub pvs: bool,              // moves after the first only shown to be no better, with a null window
+}
+
+impl Default for SearchOptions {
+    fn default() -> SearchOptions {
+        SearchOptions {
+            hash_move: true,
+            capture_ordering: true,
+            see_ordering: true,
+            killers: true,
+            countermoves: true,
+            history: true,
+            null_move: true,
+            late_move_reductions: true,
+            futility: true,
+            reverse_futility: true,
+            aspiration: true,
+            check_extensions: true,
+            pvs: true,
+        }
+    }
+}
+
+impl SearchOptions {
+    // the same search with the moves in the order they were generated
+    pub fn unordered() -> SearchOptions {
+        SearchOptions {
+            hash_move: false,
+            capture_ordering: false,
+            see_ordering: false,
+            killers: false,
+            countermoves: false,
+            history: false,
+            ..SearchOptions::default()
+        }
+    }
+
+    // The names the command line tools use for the options.
+    pub const NAMES: [&'static str; 13] = [
+        "hash-move",
+        "captures",
+        "see",
+        "killers",
+        "countermoves",
+        "history",
+        "null-move",
+        "lmr",
+        "futility",
+        "reverse-futility",
+        "aspiration",
+        "check-extensions",
+        "pvs",
+    ];
+
+    // turns an option on or off by its name, false for a name there is not
+    pub fn set(&mut self, name: &str, on: bool) -> bool {
+        let option = match name {
+            "hash-move" => &mut self.hash_move,
+            "captures" => &mut self.capture_ordering,
+            "see" => &mut self.see_ordering,
+            "killers" => &mut self.killers,
+            "countermoves" => &mut self.countermoves,
+            "history" => &mut self.history,
+            "null-move" => &mut self.null_move,
+            "lmr" => &mut self.late_move_reductions,
+            "futility" => &mut self.futility,
+            "reverse-futility" => &mut self.reverse_futility,
+            "aspiration" => &mut self.aspiration,
+            "check-extensions" => &mut self.check_extensions,
+            "pvs" => &mut self.pvs,
+            _ => return false,
+        };
+        *option = on;
+        true
+    }
+
+    // plain alpha-beta, with nothing pruned, reduced or extended
+    pub fn plain() -> SearchOptions {
+        SearchOptions {
+            null_move: false,
+            late_move_reductions: false,
+            futility: false,
+            reverse_futility: false,
+            aspiration: false,
+            check_extensions: false,
+            pvs: false,
+            ..SearchOptions::unordered()
+        }
+    }
+}
+
+// When to stop. Any that are set can end the search, with none it goes on
+// until the stop flag is raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    pub time_manager: Option<TimeManager>, // for playing on a clock
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> SearchLimits {
+        SearchLimits { depth: Some(depth), ..Default::default() }
+    }
+
+    pub fn time(time: Duration) -> SearchLimits {
+        SearchLimits { time: Some(time), ..Default::default() }
+    }
+
+    pub fn clock(time_manager: TimeManager) -> SearchLimits {
+        SearchLimits {
+            time_manager: Some(time_manager),
+            ..Default::default()
+        }
+    }
+}
+
+// The outcome of the deepest search that finished. The score is in
+// centipawns for the side to move.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    pub time: Duration,
+    pub pv: Vec<Move>,
+    pub rank: usize, // 1 for the best line when several are searched
+}
+
+impl SearchResult {
+    // moves to a forced mate, negative when the side to move gets mated
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+pub fn mate_in(score: i32) -> Option<i32> {
+    let plies = MATE - score.abs();
+    if plies > MAX_PLY as i32 {
+        return None;
+    }
+    let moves = (plies + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
+// told about every iteration as it finishes, to show the search going on
+type Reporter = Arc<dyn Fn(&SearchResult) + Send + Sync>;
+
+// Iterative deepening alpha-beta over the legal moves, with a capture search
+// at the leaves so it does not stop in the middle of an exchange. What it
+// learns is kept from one search to the next until new_game.
+//
+// With more than one thread the others search the same position alongside
+// it, each on its own copy of the board, and help only by filling the
+// transposition table they all share. Clones share the table too.
+#[derive(Clone)]
+pub struct Searcher {
+    pub options: SearchOptions,
+    tt: Arc<TranspositionTable>,
+    orderer: MoveOrderer,
+    time_source: Arc<dyn TimeSource>,
+    reporter: Option<Reporter>,
+    nodes: u64,
+    node_limit: Option<u64>,
+    started: Duration,
+    deadline: Option<Duration>, // time after the start to stop at
+    time_manager: Option<TimeManager>, // the clock's limits, its hard one checked every few nodes
+    stop: Arc<AtomicBool>,
+    stopped: bool,
+    path: Vec<u64>, // keys of the positions before the one being searched
+    threads: usize,
+    multi_pv: usize,
+    excluded: Vec<Move>, // root moves already given as better lines at this depth
+    verifying: bool,     // inside a null move verification, where no side may pass
+    pub use_tablebases: bool,
+    eval: Arc<EvalParams>,
+}
+
+impl Default for Searcher {
+    fn default() -> Searcher {
+        Searcher {
+            options: SearchOptions::default(),
+            tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            orderer: MoveOrderer::default(),
+            time_source: Arc::new(RealTime::new()),
+            reporter: None,
+            nodes: 0,
+            node_limit: None,
+            started: Duration::ZERO,
+            deadline: None,
+            time_manager: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            stopped: false,
+            path: Vec::new(),
+            threads: 1,
+            multi_pv: 1,
+            excluded: Vec::new(),
+            verifying: false,
+            use_tablebases: true,
+            eval: Arc::new(EvalParams::default()),
+        }
+    }
+}
+
+impl fmt::Debug for Searcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Searcher")
+            .field("options", &self.options)
+            .field("time_source", &self.time_source)
+            .field("nodes", &self.nodes)
+            .field("threads", &self.threads)
+            .field("multi_pv", &self.multi_pv)
+            .field("use_tablebases", &self.use_tablebases)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Searcher {
+    pub fn new() -> Searcher {
+        Searcher::default()
+    }
+
+    pub fn with_options(options: SearchOptions) -> Searcher {
+        Searcher { options, ..Searcher::default() }
+    }
+
+    // resizes the transposition table, which empties it
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.tt = Arc::new(TranspositionTable::new(megabytes));
+    }
+
+    // how many threads search, at least one
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // how many of the best moves search_lines gives, each with its own line
+    pub fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1);
+    }
+
+    pub fn multi_pv(&self) -> usize {
+        self.multi_pv
+    }
+
+    // evaluates with these weights rather than the built in ones
+    pub fn set_eval_params(&mut self, params: EvalParams) {
+        self.eval = Arc::new(params);
+    }
+
+    pub fn eval_params(&self) -> &EvalParams {
+        &self.eval
+    }
+
+    // forgets everything learned, for when the positions to come have nothing
+    // to do with the last ones
+    pub fn new_game(&mut self) {
+        self.tt.clear();
+        self.orderer.clear();
+    }
+
+    // where the time limits are measured from, the real time unless a test
+    // wants a clock of its own
+    pub fn set_time_source(&mut self, source: Arc<dyn TimeSource>) {
+        self.time_source = source;
+    }
+
+    pub fn set_reporter(&mut self, reporter: Option<Reporter>) {
+        self.reporter = reporter;
+    }
+
+    // Setting the flag makes a running search give up and answer with what
+    // it has, from another thread. It is lowered again when the search
+    // returns, and one raised before the search starts stops it at once.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    // searches the game's current position, knowing which positions came
+    // before so it can see repetitions coming
+    pub fn search_game(&mut self, game: &Game, limits: SearchLimits) -> SearchResult {
+        let mut board = game.start().clone();
+        let mut history = Vec::new();
+        for record in game.moves() {
+            history.push(board.polyglot_key());
+            board.apply_move(&record.mv);
+        }
+        self.search_with_history(&board, &history, limits)
+    }
+
+    pub fn search(&mut self, board: &Board, limits: SearchLimits) -> SearchResult {
+        self.search_with_history(board, &[], limits)
+    }
+
+    pub fn search_with_history(&mut self, board: &Board, history: &[u64], limits: SearchLimits) -> SearchResult {
+        self.search_lines(board, history, limits).swap_remove(0)
+    }
+
+    // The best moves up to the multi_pv setting, best first, each searched
+    // to the same depth with the better ones left out. There are fewer when
+    // there are fewer legal moves, and just one when the game is over or
+    // the tablebases give the answer.
+    pub fn search_lines(&mut self, board: &Board, history: &[u64], limits: SearchLimits) -> Vec<SearchResult> {
+        let lines = self.iterate(board, history, limits);
+        self.stop.store(false, Ordering::SeqCst);
+        lines
+    }
+
+    fn iterate(&mut self, board: &Board, history: &[u64], limits: SearchLimits) -> Vec<SearchResult> {
+        self.started = self.time_source.now();
+        self.nodes = 0;
+        self.node_limit = limits.nodes;
+        self.deadline = limits.time;
+        self.time_manager = limits.time_manager;
+        self.stopped = false;
+        self.path = history.to_vec();
+        self.orderer.new_search();
+
+        let legal_moves = board.legal_moves();
+        let mut result = SearchResult {
+            best_move: legal_moves.first().copied(),
+            rank: 1,
+            ..Default::default()
+        };
+        if legal_moves.is_empty() {
+            result.score = if board.is_in_check(board.side_to_move()) { -MATE } else { 0 };
+            return vec![result];
+        }
+
+        if self.use_tablebases {
+            if let Some((mv, score)) = probe_root(board) {
+                result.best_move = Some(mv);
+                result.pv = vec![mv];
+                result.score = score;
+                result.depth = 1;
+                return vec![result];
+            }
+        }
+
+        let count = self.multi_pv.min(legal_moves.len());
+        if self.threads == 1 {
+            return self.deepen(board, limits, 1, count, vec![result]);
+        }
+        // The helpers start on alternate depths so they are not all a step
+        // behind the main thread on the same one. They have no limits of
+        // their own and stop when it does, and its answer is the one given.
+        let done = Arc::new(AtomicBool::new(false));
+        thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|i| {
+                    let mut helper = self.helper(done.clone());
+                    let board = board.clone();
+                    let lines = vec![result.clone()];
+                    scope.spawn(move || {
+                        helper.deepen(&board, SearchLimits::default(), 1 + i as u32 % 2, count, lines);
+                        helper.nodes
+                    })
+                })
+                .collect();
+            let mut lines = self.deepen(board, limits, 1, count, vec![result]);
+            done.store(true, Ordering::SeqCst);
+            let helper_nodes: u64 = helpers
+                .into_iter()
+                .map(|helper| helper.join().expect("a search thread panicked"))
+                .sum();
+            lines.iter_mut().for_each(|line| line.nodes += helper_nodes);
+            lines
+        })
+    }
+
+    // a copy for another thread that stops when the flag is raised
+    fn helper(&self, done: Arc<AtomicBool>) -> Searcher {
+        Searcher {
+            reporter: None,
+            node_limit: None,
+            deadline: None,
+            time_manager: None,
+            stop: done,
+            threads: 1,
+            ..self.clone()
+        }
+    }
+
+    // Each depth searches the lines one after another, each time without the
+    // moves the ones before it started with, then ranks them by score. A
+    // depth left unfinished is thrown away.
+    fn deepen(
+        &mut self,
+        board: &Board,
+        limits: SearchLimits,
+        first_depth: u32,
+        count: usize,
+        mut lines: Vec<SearchResult>,
+    ) -> Vec<SearchResult> {
+        let mut time_manager = limits.time_manager;
+        let max_depth = limits.depth.unwrap_or(MAX_PLY).min(MAX_PLY);
+        for depth in first_depth..=max_depth {
+            let mut found: Vec<SearchResult> = Vec::with_capacity(count);
+            self.excluded.clear();
+            while found.len() < count {
+                let last_score = lines.get(found.len()).map_or(0, |line| line.score);
+                let mut pv = Vec::new();
+                let score = self.search_root(board, depth as i32, last_score, &mut pv);
+                if self.stopped || pv.is_empty() {
+                    break;
+                }
+                self.excluded.push(pv[0]);
+                found.push(SearchResult {
+                    best_move: Some(pv[0]),
+                    score,
+                    depth,
+                    pv,
+                    ..Default::default()
+                });
+            }
+            if self.stopped || found.is_empty() {
+                break;
+            }
+            found.sort_by_key(|line| std::cmp::Reverse(line.score));
+            let time = self.elapsed();
+            for (rank, line) in found.iter_mut().enumerate() {
+                line.rank = rank + 1;
+                line.nodes = self.nodes;
+                line.time = time;
+            }
+            let best_move_changed = depth > 1 && found[0].best_move != lines[0].best_move;
+            lines = found;
+            if let Some(reporter) = &self.reporter {
+                lines.iter().for_each(|line| reporter(line));
+            }
+            // nothing deeper can change forced mates already found
+            if lines
+                .iter()
+                .all(|line| mate_in(line.score).is_some_and(|moves| moves.unsigned_abs() <= depth / 2))
+            {
+                break;
+            }
+            if let Some(manager) = &mut time_manager {
+                manager.iteration_done(best_move_changed);
+                if manager.should_stop(time) {
+                    break;
+                }
+            }
+        }
+        self.excluded.clear();
+        let time = self.elapsed();
+        for line in &mut lines {
+            line.nodes = self.nodes;
+            line.time = time;
+        }
+        lines
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.time_source.now().saturating_sub(self.started)
+    }
+
+    // Searches with a window round the last iteration's score, widening it
+    // on whichever side the score falls out until it lands inside.
+    fn search_root(&mut self, board: &Board, depth: i32, last_score: i32, pv: &mut Vec<Move>) -> i32 {
+        if !self.options.aspiration || depth < 4 || last_score.abs() >= TABLEBASE_WIN - MAX_PLY as i32 {
+            return self.negamax(board, depth, 0, -MATE - 1, MATE + 1, None, pv);
+        }
+        let mut window = ASPIRATION_WINDOW;
+        let mut alpha = last_score - window;
+        let mut beta = last_score + window;
+        loop {
+            pv.clear();
+            let score = self.negamax(board, depth, 0, alpha, beta, None, pv);
+            if self.stopped {
+                return score;
+            }
+            window *= 2;
+            if score <= alpha {
+                alpha = (score - window).max(-MATE - 1);
+            } else if score >= beta {
+                beta = (score + window).min(MATE + 1);
+            } else {
+                return score;
+            }
+        }
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        if self.nodes.is_multiple_of(CHECK_EVERY) {
+            let past_deadline = self.deadline.is_some_and(|deadline| self.elapsed() >= deadline)
+                || self.time_manager.is_some_and(|manager| manager.must_stop(self.elapsed()));
+            let past_nodes = self.node_limit.is_some_and(|limit| self.nodes >= limit);
+            if past_deadline || past_nodes || self.stop.load(Ordering::Relaxed) {
+                self.stopped = true;
+            }
+        }
+        self.stopped
+    }
+
+    // A null move is passed down as no previous move, so two never come in a
+    // row and nothing is learned from it as a move to answer.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &Board,
+        mut depth: i32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+        previous: Option<Move>,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+
+        let key = board.polyglot_key();
+        if ply > 0 {
+            if self.path.contains(&key) || board.halfmove_clock >= 100 || board.is_insufficient_material() {
+                return 0;
+            }
+            if self.use_tablebases {
+                if let Some(score) = probe_tree(board, ply) {
+                    return score;
+                }
+            }
+        }
+        let in_check = board.is_in_check(board.side_to_move());
+        if in_check && self.options.check_extensions && ply < MAX_PLY / 2 {
+            depth += 1;
+        }
+        if depth <= 0 || ply >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        let entry = self.tt.probe(key, ply);
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+            if usable {
+                return entry.score;
+            }
+        }
+
+        let pv_node = beta - alpha > 1;
+        let decided = alpha.abs() >= TABLEBASE_WIN - MAX_PLY as i32 || beta.abs() >= TABLEBASE_WIN - MAX_PLY as i32;
+        let static_eval = if in_check { -MATE } else { evaluate_with(board, &self.eval) };
+
+        // so far ahead that even giving some back keeps it above beta
+        if self.options.reverse_futility
+            && !pv_node
+            && !in_check
+            && !decided
+            && depth <= 3
+            && static_eval - REVERSE_FUTILITY_MARGIN * depth >= beta
+        {
+            return static_eval;
+        }
+
+        // Passing still being too good for the opponent means a real move
+        // would be too. Not with only pawns left, where passing might be the
+        // only good move there is.
+        if self.options.null_move
+            && !self.verifying
+            && !pv_node
+            && !in_check
+            && !decided
+            && ply > 0
+            && previous.is_some()
+            && depth >= 3
+            && static_eval >= beta
+            && has_pieces(board)
+        {
+            let reduction = 2 + depth / 6;
+            let mut null_pv = Vec::new();
+            self.path.push(key);
+            let score = -self.negamax(&null_move(board), depth - 1 - reduction, ply + 1, -beta, -beta + 1, None, &mut null_pv);
+            self.path.pop();
+            if self.stopped {
+                return 0;
+            }
+            // In zugzwang passing beats every real move, so the cut is only
+            // taken once a search to the same reduced depth, with real moves
+            // and no passing anywhere below, agrees.
+            if score >= beta {
+                self.verifying = true;
+                let verified = self.negamax(board, depth - 1 - reduction, ply, beta - 1, beta, None, &mut null_pv);
+                self.verifying = false;
+                if verified >= beta {
+                    // a mate found this way is not to be trusted
+                    return if score >= TABLEBASE_WIN - MAX_PLY as i32 { beta } else { score };
+                }
+            }
+            if self.stopped {
+                return 0;
+            }
+        }
+
+        let mut moves = board.legal_moves();
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+        if ply == 0 {
+            moves.retain(|mv| !self.excluded.contains(mv));
+        }
+        let hash_move = entry.and_then(|entry| entry.best_move);
+        let moves = self.orderer.order(board, moves, hash_move, previous, ply, &self.options);
+        let futile = self.options.futility
+            && !pv_node
+            && !in_check
+            && !decided
+            && depth < FUTILITY_MARGINS.len() as i32
+            && static_eval + FUTILITY_MARGINS[depth as usize] <= alpha;
+
+        self.path.push(key);
+        let original_alpha = alpha;
+        let mut best = -MATE - 1;
+        let mut best_move = None;
+        for (searched, mv) in moves.into_iter().enumerate() {
+            let quiet = !board.is_capture(&mv) && mv.promotion.is_none();
+            let mut child = board.clone();
+            child.apply_move(&mv);
+            let gives_check = child.is_in_check(child.side_to_move());
+            if futile && quiet && !gives_check && searched > 0 {
+                continue;
+            }
+
+            let mut child_pv = Vec::new();
+            let score = if searched == 0 || !self.options.pvs {
+                -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, Some(mv), &mut child_pv)
+            } else {
+                // later moves are expected to be worse, which a null window
+                // shows cheaply, and the quiet ones a ply or two shallower
+                let reduction = if self.options.late_move_reductions
+                    && depth >= 3
+                    && searched >= 3
+                    && quiet
+                    && !in_check
+                    && !gives_check
+                {
+                    1 + i32::from(searched >= 6 && depth >= 6)
+                } else {
+                    0
+                };
+                let mut score =
+                    -self.negamax(&child, depth - 1 - reduction, ply + 1, -alpha - 1, -alpha, Some(mv), &mut child_pv);
+                if score > alpha && reduction > 0 && !self.stopped {
+                    child_pv.clear();
+                    score = -self.negamax(&child, depth - 1, ply + 1, -alpha - 1, -alpha, Some(mv), &mut child_pv);
+                }
+                if score > alpha && score < beta && !self.stopped {
+                    child_pv.clear();
+                    score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, Some(mv), &mut child_pv);
+                }
+                score
+            };
+            if self.stopped {
+                break;
+            }
+            if score > best {
+                best = score;
+                best_move = Some(mv);
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(mv);
+                    pv.extend(child_pv);
+                }
+                if score >= beta {
+                    if quiet {
+                        self.orderer.cutoff(board, &mv, previous, ply, depth);
+                    }
+                    break;
+                }
+            }
+        }
+        self.path.pop();
+        // every move was too weak to bother with
+        if best_move.is_none() {
+            return alpha;
+        }
+
+        // with moves left out the root's best is not the position's
+        if !self.stopped && (ply > 0 || self.excluded.is_empty()) {
+            let bound = if best >= beta {
+                Bound::Lower
+            } else if best > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.tt.store(Entry { key, best_move, depth, score: best, bound }, ply);
+        }
+        best
+    }
+
+    // only captures and promotions, standing on the evaluation when none help
+    fn quiescence(&mut self, board: &Board, ply: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+
+        let stand_pat = evaluate_with(board, &self.eval);
+        if stand_pat >= beta || ply >= MAX_PLY {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut best = stand_pat;
+        // always most valuable victim first, without it this search alone can
+        // run to millions of nodes in a busy position
+        let mut captures: Vec<Move> = board
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| board.is_capture(mv) || mv.promotion.is_some())
+            .collect();
+        captures.sort_by_key(|mv| std::cmp::Reverse(mvv_lva(board, mv)));
+        for mv in captures {
+            let mut child = board.clone();
+            child.apply_move(&mv);
+            let score = -self.quiescence(&child, ply + 1, -beta, -alpha);
+            if self.stopped {
+                break;
+            }
+            if score > best {
+                best = score;
+                alpha = alpha.max(score);
+                if score >= beta {
+                    break;
+                }
+            }
+        }
+        best
+    }
+}
+
+// the position with the other side to move and nothing else changed
+fn null_move(board: &Board) -> Board {
+    let mut passed = board.clone();
+    passed.can_en_pessant = [(-1, -1), (-1, -1)];
+    passed.en_pessant_square = None;
+    passed.halfmove_clock += 1;
+    if passed.turn == 1 {
+        passed.turn_number = passed.turn_number.saturating_add(1);
+    }
+    passed.turn = 1 - passed.turn;
+    passed
+}
+
+// whether the side to move has anything besides pawns and the king
+fn has_pieces(board: &Board) -> bool {
+    let color = board.side_to_move();
+    board.board.iter().flatten().flatten().any(|piece| {
+        piece.color == color && !matches!(piece.piece_type, PieceType::Pawn(_) | PieceType::King(_))
+    })
+}
+
+#[cfg(feature = "syzygy")]
+fn probe_root(board: &Board) -> Option<(Move, i32)> {
+    crate::syzygy::probe_root(board)
+}
+
+#[cfg(not(feature = "syzygy"))]
+fn probe_root(_board: &Board) -> Option<(Move, i32)> {
+    None
+}
+
+#[cfg(feature = "syzygy")]
+fn probe_tree(board: &Board, ply: u32) -> Option<i32> {
+    crate::syzygy::probe_tree(board, ply)
+}
+
+#[cfg(not(feature = "syzygy"))]
+fn probe_tree(_board: &Board, _ply: u32) -> Option<i32> {
+    None
+}
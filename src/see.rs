@@ -0,0 +1,79 @@
+use crate::eval::{piece_value, PAWN_VALUE, QUEEN_VALUE};
+use crate::{Board, Color, Move, Piece, PieceType};
+
+impl Board {
+    // Static exchange evaluation: what the move wins or loses in centipawns
+    // once both sides have made every capture on its square that pays. Each
+    // side captures with its cheapest piece first, and taking a piece off
+    // uncovers the sliders lined up behind it. Pins are not looked at.
+    pub fn see(&self, mv: &Move) -> i32 {
+        let target = mv.to;
+        let mover = match self.board[mv.from.0][mv.from.1] {
+            Some(piece) => piece,
+            None => return 0,
+        };
+        if self.is_castling(mv) {
+            return 0;
+        }
+
+        // gains[i] is what the side making the i-th capture has won if the
+        // exchange stops right after it
+        let mut gains = vec![match self.board[target.0][target.1] {
+            Some(captured) => piece_value(captured.piece_type),
+            None if self.is_en_pessant(mv) => PAWN_VALUE,
+            None => 0,
+        }];
+        let mut on_square = piece_value(mover.piece_type);
+        if let Some(promotion) = mv.promotion {
+            gains[0] += piece_value(promotion) - PAWN_VALUE;
+            on_square = piece_value(promotion);
+        }
+
+        // the pieces are taken off a copy as they capture, so the board only
+        // says who can still reach the square
+        let mut scratch = self.clone();
+        if self.is_en_pessant(mv) {
+            scratch.board[mv.from.0][target.1] = None;
+        }
+        scratch.board[mv.from.0][mv.from.1] = None;
+        scratch.board[target.0][target.1] = Some(mover);
+
+        let mut side = mover.color.opposite();
+        while let Some((from, attacker)) = scratch.least_valuable_attacker(target, side) {
+            scratch.board[from.0][from.1] = None;
+            // the king may only take last, when nothing can take it back
+            if matches!(attacker.piece_type, PieceType::King(_))
+                && scratch.least_valuable_attacker(target, side.opposite()).is_some()
+            {
+                break;
+            }
+
+            let mut gain = on_square - gains[gains.len() - 1];
+            on_square = piece_value(attacker.piece_type);
+            if matches!(attacker.piece_type, PieceType::Pawn(_)) && (target.0 == 0 || target.0 == 7) {
+                gain += QUEEN_VALUE - PAWN_VALUE;
+                on_square = QUEEN_VALUE;
+            }
+            gains.push(gain);
+            scratch.board[target.0][target.1] = Some(attacker);
+            side = side.opposite();
+        }
+
+        // going back through the captures, each side stops when going on
+        // would lose it more
+        for i in (1..gains.len()).rev() {
+            gains[i - 1] = -(-gains[i - 1]).max(gains[i]);
+        }
+        gains[0]
+    }
+
+    fn least_valuable_attacker(&self, square: (usize, usize), by: Color) -> Option<((usize, usize), Piece)> {
+        self.find_attackers(square, by, false)
+            .into_iter()
+            .filter_map(|(rank, file)| self.board[rank][file].map(|piece| ((rank, file), piece)))
+            .min_by_key(|(_, piece)| match piece.piece_type {
+                PieceType::King(_) => i32::MAX,
+                piece_type => piece_value(piece_type),
+            })
+    }
+}
@@ -0,0 +1,89 @@
+// The schema is described in the README, keep the two in step.
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{Board, Game, GameResult, MoveRecord, Termination};
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let fen = String::deserialize(deserializer)?;
+        Board::from_fen(&fen).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for GameResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for GameResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<GameResult, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+// what a game is stored as
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GameData {
+    event: String,
+    site: String,
+    date: String,
+    round: String,
+    white: String,
+    black: String,
+    tags: Vec<(String, String)>,
+    start: Board,
+    moves: Vec<MoveRecord>,
+    result: GameResult,
+    termination: Option<Termination>,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let data = GameData {
+            event: self.event.clone(),
+            site: self.site.clone(),
+            date: self.date.clone(),
+            round: self.round.clone(),
+            white: self.white.clone(),
+            black: self.black.clone(),
+            tags: self.tags.clone(),
+            start: self.start().clone(),
+            moves: self.moves().to_vec(),
+            result: self.result(),
+            termination: self.termination(),
+        };
+        data.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Game, D::Error> {
+        let data = GameData::deserialize(deserializer)?;
+        let mut game = Game::from_position(data.start);
+        game.event = data.event;
+        game.site = data.site;
+        game.date = data.date;
+        game.round = data.round;
+        game.white = data.white;
+        game.black = data.black;
+        game.tags = data.tags;
+        for record in data.moves {
+            if !game.board().is_legal_move(&record.mv) {
+                return Err(de::Error::custom(format!("illegal move {}", record.san)));
+            }
+            game.push_record(record);
+        }
+        game.set_result(data.result, data.termination);
+        Ok(game)
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+// Wins, draws and losses from one side's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+// the 97.5% point of the normal distribution, for 95% error bars
+const Z_95: f64 = 1.959964;
+
+impl MatchScore {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    // points per game, a draw counting half
+    pub fn ratio(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    // the Elo difference that would give this score
+    pub fn elo(&self) -> f64 {
+        elo_from_ratio(self.ratio())
+    }
+
+    // half the width of the 95% confidence interval round elo()
+    pub fn elo_error(&self) -> f64 {
+        let games = self.games() as f64;
+        if games == 0.0 {
+            return f64::INFINITY;
+        }
+        let ratio = self.ratio();
+        let deviation = (self.variance() / games).sqrt();
+        let low = elo_from_ratio(ratio - Z_95 * deviation);
+        let high = elo_from_ratio(ratio + Z_95 * deviation);
+        if !low.is_finite() || !high.is_finite() {
+            return f64::INFINITY;
+        }
+        (high - low) / 2.0
+    }
+
+    // the variance of one game's points
+    fn variance(&self) -> f64 {
+        let games = self.games() as f64;
+        let wins = self.wins as f64 / games;
+        let draws = self.draws as f64 / games;
+        wins + draws / 4.0 - self.ratio().powi(2)
+    }
+}
+
+impl fmt::Display for MatchScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins, self.draws, self.losses)
+    }
+}
+
+// The Elo difference for a score ratio, infinite for a clean sweep either
+// way.
+pub fn elo_from_ratio(ratio: f64) -> f64 {
+    if ratio <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if ratio >= 1.0 {
+        return f64::INFINITY;
+    }
+    400.0 * (ratio / (1.0 - ratio)).log10()
+}
+
+fn ratio_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+// A sequential probability ratio test between the hypotheses that the
+// difference is elo0 and that it is elo1, stopping a match as soon as the
+// games played decide it. alpha is the chance of passing a change that is
+// really elo0, beta of failing one that is really elo1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SprtStatus {
+    Pass,     // elo1 accepted
+    Fail,     // elo0 accepted
+    Continue, // not decided yet
+}
+
+impl fmt::Display for SprtStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            SprtStatus::Pass => "passed",
+            SprtStatus::Fail => "failed",
+            SprtStatus::Continue => "undecided",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Sprt {
+        Sprt { elo0, elo1, alpha, beta }
+    }
+
+    // the log likelihood ratio at which the test fails and passes
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    // The log likelihood ratio of elo1 over elo0 for the score, taking the
+    // games' points as normally distributed. Half a game is added to each
+    // outcome so that a run of only wins or only draws has a variance.
+    pub fn llr(&self, score: &MatchScore) -> f64 {
+        if score.games() == 0 {
+            return 0.0;
+        }
+        let (wins, draws, losses) = (score.wins as f64 + 0.5, score.draws as f64 + 0.5, score.losses as f64 + 0.5);
+        let games = wins + draws + losses;
+        let ratio = (wins + draws / 2.0) / games;
+        let variance = (wins + draws / 4.0) / games - ratio.powi(2);
+        let (ratio0, ratio1) = (ratio_from_elo(self.elo0), ratio_from_elo(self.elo1));
+        score.games() as f64 * (ratio1 - ratio0) * (2.0 * ratio - ratio0 - ratio1) / (2.0 * variance)
+    }
+
+    pub fn status(&self, score: &MatchScore) -> SprtStatus {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtStatus::Pass
+        } else if llr <= lower {
+            SprtStatus::Fail
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt::Write;
+
+use crate::{Board, Color, Piece, PieceType};
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const HIGHLIGHT: &str = "#cdd26a";
+const ARROW: &str = "#15781b";
+const COORDINATES: &str = "#333333";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    pub size: u32, // width and height of the whole image in pixels
+    pub flipped: bool,
+    pub coordinates: bool,
+    pub highlighted: Vec<(usize, usize)>,
+    pub arrows: Vec<((usize, usize), (usize, usize))>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            size: 400,
+            flipped: false,
+            coordinates: true,
+            highlighted: Vec::new(),
+            arrows: Vec::new(),
+        }
+    }
+}
+
+// Piece outlines drawn on a 45x45 grid. Each line is one svg element, white
+// pieces fill them white and black pieces black.
+fn piece_outline(piece_type: PieceType) -> &'static [&'static str] {
+    match piece_type {
+        PieceType::Pawn(_) => &[
+            r#"<circle cx="22.5" cy="12" r="5"/>"#,
+            r#"<path d="M17 19h11l-2 4 4 11H15l4-11z"/>"#,
+            r#"<rect x="11" y="34" width="23" height="5" rx="1.5"/>"#,
+        ],
+        PieceType::Rook(_) => &[
+            r#"<path d="M11 9h4v3h4V9h7v3h4V9h4v8l-3 3v11l3 3H11l3-3V20l-3-3z"/>"#,
+            r#"<rect x="9" y="34" width="27" height="5" rx="1.5"/>"#,
+        ],
+        PieceType::Bishop => &[
+            r#"<circle cx="22.5" cy="8" r="2.5"/>"#,
+            r#"<path d="M22.5 11c-6 4-8 10-6 16h12c2-6 0-12-6-16z"/>"#,
+            r#"<path d="M15 29h15l-2 4H17z"/>"#,
+            r#"<rect x="9" y="34" width="27" height="5" rx="1.5"/>"#,
+            r#"<path d="M20 21l5-5" data-detail="1"/>"#,
+        ],
+        PieceType::Knight => &[
+            r#"<path d="M14 35c0-8 2-12 6-15-3 0-6 1-8 3-1-2 0-5 3-8l2-6 3 3c6 0 11 4 12 12 1 6 0 9-1 11z"/>"#,
+            r#"<rect x="9" y="34" width="27" height="5" rx="1.5"/>"#,
+            r#"<circle cx="19" cy="14" r="1" data-detail="1"/>"#,
+        ],
+        PieceType::Queen => &[
+            r#"<path d="M9 13l5 17h17l5-17-8 9-5.5-12-5.5 12z"/>"#,
+            r#"<circle cx="9" cy="12" r="2"/>"#,
+            r#"<circle cx="22.5" cy="9" r="2"/>"#,
+            r#"<circle cx="36" cy="12" r="2"/>"#,
+            r#"<path d="M13 30h19l-1 4H14z"/>"#,
+            r#"<rect x="9" y="34" width="27" height="5" rx="1.5"/>"#,
+        ],
+        PieceType::King(_) => &[
+            r#"<path d="M22.5 5v8M19 8.5h7" fill="none"/>"#,
+            r#"<path d="M22.5 14c-3 0-4 3-4 5-5-3-10 0-8 5l4 6h16l4-6c2-5-3-8-8-5 0-2-1-5-4-5z"/>"#,
+            r#"<path d="M14 30h17l-1 4H15z"/>"#,
+            r#"<rect x="9" y="34" width="27" height="5" rx="1.5"/>"#,
+        ],
+    }
+}
+
+fn piece_id(piece: &Piece) -> String {
+    let color = if piece.color.is_white() { 'w' } else { 'b' };
+    format!("{}{}", color, piece.piece_type.ret_type_as_char().to_ascii_lowercase())
+}
+
+impl Board {
+    // A standalone svg of the position, pieces are drawn from shapes defined in
+    // the file itself so nothing else needs to be shipped with it.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = options.size as f64;
+        // with coordinates a half square border goes around the board
+        let square = if options.coordinates { size / 9.0 } else { size / 8.0 };
+        let margin = if options.coordinates { square / 2.0 } else { 0.0 };
+
+        // where the top left corner of a square ends up on the image
+        let corner = |(rank, file): (usize, usize)| {
+            let (row, column) = if options.flipped { (7 - rank, 7 - file) } else { (rank, file) };
+            (margin + column as f64 * square, margin + row as f64 * square)
+        };
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            options.size
+        );
+
+        svg.push_str("<defs>\n");
+        let mut defined: Vec<String> = Vec::new();
+        for piece in self.board.iter().flatten().flatten() {
+            let id = piece_id(piece);
+            if defined.contains(&id) {
+                continue;
+            }
+            let (fill, detail) = match piece.color {
+                Color::White => ("#ffffff", "#000000"),
+                Color::Black => ("#000000", "#ffffff"),
+            };
+            let _ = writeln!(
+                svg,
+                r##"<g id="{}" fill="{}" stroke="#000000" stroke-width="1.5" stroke-linejoin="round">"##,
+                id, fill
+            );
+            for element in piece_outline(piece.piece_type) {
+                let element = element.replace(r#"data-detail="1""#, &format!(r#"stroke="{}""#, detail));
+                let _ = writeln!(svg, "{}", element);
+            }
+            svg.push_str("</g>\n");
+            defined.push(id);
+        }
+        let _ = writeln!(
+            svg,
+            r#"<marker id="arrowhead" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="3" markerHeight="3" orient="auto"><path d="M0 0L10 5 0 10z" fill="{}"/></marker>"#,
+            ARROW
+        );
+        svg.push_str("</defs>\n");
+
+        if options.coordinates {
+            let _ = writeln!(svg, r##"<rect width="{0}" height="{0}" fill="#ffffff"/>"##, options.size);
+        }
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let (x, y) = corner((rank, file));
+                let color = if options.highlighted.contains(&(rank, file)) {
+                    HIGHLIGHT
+                } else if (rank + file) % 2 == 0 {
+                    LIGHT_SQUARE
+                } else {
+                    DARK_SQUARE
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+                    x, y, square, square, color
+                );
+            }
+        }
+
+        if options.coordinates {
+            let font = square * 0.3;
+            for i in 0..8 {
+                let (x, _) = corner((0, i));
+                let (_, y) = corner((i, 0));
+                let file = (b'a' + i as u8) as char;
+                let rank = 8 - i;
+                for label_y in [margin / 2.0, size - margin / 2.0] {
+                    let _ = writeln!(
+                        svg,
+                        r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}" font-family="sans-serif" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                        x + square / 2.0, label_y, font, COORDINATES, file
+                    );
+                }
+                for label_x in [margin / 2.0, size - margin / 2.0] {
+                    let _ = writeln!(
+                        svg,
+                        r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}" font-family="sans-serif" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                        label_x, y + square / 2.0, font, COORDINATES, rank
+                    );
+                }
+            }
+        }
+
+        let scale = square / 45.0;
+        for rank in 0..8 {
+            for file in 0..8 {
+                if let Some(piece) = &self.board[rank][file] {
+                    let (x, y) = corner((rank, file));
+                    let _ = writeln!(
+                        svg,
+                        r##"<use xlink:href="#{0}" href="#{0}" transform="translate({1:.2},{2:.2}) scale({3:.4})"/>"##,
+                        piece_id(piece), x, y, scale
+                    );
+                }
+            }
+        }
+
+        for &(start, end) in &options.arrows {
+            let (x1, y1) = corner(start);
+            let (x2, y2) = corner(end);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}" stroke-width="{:.2}" stroke-linecap="round" opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+                x1 + square / 2.0,
+                y1 + square / 2.0,
+                x2 + square / 2.0,
+                y2 + square / 2.0,
+                ARROW,
+                square / 6.0
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+
+use shakmaty::fen::Fen;
+use shakmaty::uci::UciMove;
+use shakmaty::{CastlingMode, Chess};
+use shakmaty_syzygy::{Tablebase, Wdl as SyzygyWdl};
+
+use crate::search::TABLEBASE_WIN;
+use crate::{Board, Move};
+
+// The tables are opened once for the whole program, the way engines do it,
+// so every board and every search thread sees the same ones.
+static TABLEBASE: RwLock<Option<Tablebase<Chess>>> = RwLock::new(None);
+
+// Adds the Syzygy .rtbw/.rtbz files found in the directory, giving how many
+// there were. Can be called again for more directories.
+pub fn open_tablebases<P: AsRef<Path>>(dir: P) -> io::Result<usize> {
+    let mut tablebase = TABLEBASE.write().unwrap_or_else(|e| e.into_inner());
+    let tablebase = tablebase.get_or_insert_with(Tablebase::new);
+    tablebase.add_directory(dir)
+}
+
+// the most pieces, kings included, that the open tables cover, 0 with none open
+pub fn tablebase_pieces() -> usize {
+    let tablebase = TABLEBASE.read().unwrap_or_else(|e| e.into_inner());
+    tablebase.as_ref().map_or(0, |tablebase| tablebase.max_pieces())
+}
+
+// Win, draw or loss for the side to move with best play. The cursed win and
+// blessed loss are the ones the fifty move rule turns into a draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl From<SyzygyWdl> for Wdl {
+    fn from(wdl: SyzygyWdl) -> Wdl {
+        match wdl {
+            SyzygyWdl::Loss => Wdl::Loss,
+            SyzygyWdl::BlessedLoss => Wdl::BlessedLoss,
+            SyzygyWdl::Draw => Wdl::Draw,
+            SyzygyWdl::CursedWin => Wdl::CursedWin,
+            SyzygyWdl::Win => Wdl::Win,
+        }
+    }
+}
+
+impl fmt::Display for Wdl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Wdl::Loss => "loss",
+            Wdl::BlessedLoss => "blessed loss",
+            Wdl::Draw => "draw",
+            Wdl::CursedWin => "cursed win",
+            Wdl::Win => "win",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Board {
+    // None when no table covers the position
+    pub fn probe_wdl(&self) -> Option<Wdl> {
+        let tablebase = TABLEBASE.read().unwrap_or_else(|e| e.into_inner());
+        let tablebase = tablebase.as_ref()?;
+        let position = self.tablebase_position(tablebase)?;
+        if self.halfmove_clock == 0 {
+            return tablebase.probe_wdl_after_zeroing(&position).ok().map(Wdl::from);
+        }
+        // Partway into the fifty moves the tables only say for sure when the
+        // distance to zeroing settles it, otherwise the count is worked out
+        // from the dtz.
+        let wdl = tablebase.probe_wdl(&position).ok()?;
+        if let Some(wdl) = wdl.unambiguous() {
+            return Some(wdl.into());
+        }
+        let dtz = tablebase.probe_dtz(&position).ok()?.ignore_rounding().0;
+        let within_fifty = dtz.abs() + i32::from(self.halfmove_clock) <= 100;
+        Some(match (dtz > 0, within_fifty) {
+            (true, true) => Wdl::Win,
+            (true, false) => Wdl::CursedWin,
+            (false, true) => Wdl::Loss,
+            (false, false) => Wdl::BlessedLoss,
+        })
+    }
+
+    // Plies to the next capture or pawn move that keeps the result, positive
+    // when the side to move is winning and 0 for a draw. May be one ply out,
+    // as the tables store some of them rounded.
+    pub fn probe_dtz(&self) -> Option<i32> {
+        let tablebase = TABLEBASE.read().unwrap_or_else(|e| e.into_inner());
+        let tablebase = tablebase.as_ref()?;
+        let position = self.tablebase_position(tablebase)?;
+        let dtz = tablebase.probe_dtz(&position).ok()?;
+        Some(dtz.ignore_rounding().0)
+    }
+
+    // tables are only for positions without castling and few enough pieces
+    fn tablebase_position(&self, tablebase: &Tablebase<Chess>) -> Option<Chess> {
+        let pieces = self.board.iter().flatten().flatten().count();
+        if pieces > tablebase.max_pieces() {
+            return None;
+        }
+        let fen = self.to_fen();
+        if fen.split_whitespace().nth(2) != Some("-") {
+            return None;
+        }
+        let fen = Fen::from_ascii(fen.as_bytes()).ok()?;
+        fen.into_position(CastlingMode::Standard).ok()
+    }
+}
+
+// For the search at the root: the move that keeps the tablebase result and
+// the score it is worth.
+pub(crate) fn probe_root(board: &Board) -> Option<(Move, i32)> {
+    let best = {
+        let tablebase = TABLEBASE.read().unwrap_or_else(|e| e.into_inner());
+        let tablebase = tablebase.as_ref()?;
+        let position = board.tablebase_position(tablebase)?;
+        tablebase.best_move(&position).ok()??.0
+    };
+
+    let text = UciMove::from_standard(best).to_string();
+    let mv = board.parse_coordinates(&text).ok()?;
+    Some((mv, wdl_score(board.probe_wdl()?, 0)))
+}
+
+// For the search inside the tree, only right after a capture or pawn move
+// where the answer is exact and cheap.
+pub(crate) fn probe_tree(board: &Board, ply: u32) -> Option<i32> {
+    if board.halfmove_clock != 0 {
+        return None;
+    }
+    board.probe_wdl().map(|wdl| wdl_score(wdl, ply))
+}
+
+fn wdl_score(wdl: Wdl, ply: u32) -> i32 {
+    match wdl {
+        Wdl::Win => TABLEBASE_WIN - ply as i32,
+        Wdl::Loss => -TABLEBASE_WIN + ply as i32,
+        // a shade off a draw, so the side with the chances still plays on
+        Wdl::CursedWin => 1,
+        Wdl::BlessedLoss => -1,
+        Wdl::Draw => 0,
+    }
+}
@@ -0,0 +1,219 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    array_notation_to_chess_notation, parse_square, Board, Color, Piece, PieceType, RenderOptions,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidPiece(String),
+    InvalidPieceType(String),
+    InvalidColor(String),
+    InvalidSquare(String),
+    InvalidDiagram(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidPiece(text) => write!(f, "not a piece: {:?}", text),
+            ParseError::InvalidPieceType(text) => write!(f, "not a piece type: {:?}", text),
+            ParseError::InvalidColor(text) => write!(f, "not a color: {:?}", text),
+            ParseError::InvalidSquare(text) => write!(f, "not a square: {:?}", text),
+            ParseError::InvalidDiagram(reason) => write!(f, "bad board diagram: {}", reason),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+// A square in the same (rank, file) array notation the board uses, so a8 is (0, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Square {
+    pub rank: usize,
+    pub file: usize,
+}
+
+impl From<(usize, usize)> for Square {
+    fn from(pos: (usize, usize)) -> Self {
+        Square { rank: pos.0, file: pos.1 }
+    }
+}
+
+impl From<Square> for (usize, usize) {
+    fn from(square: Square) -> Self {
+        (square.rank, square.file)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", array_notation_to_chess_notation((self.rank, self.file)))
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_square(s.trim())
+            .map(Square::from)
+            .ok_or_else(|| ParseError::InvalidSquare(s.to_string()))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::White => write!(f, "white"),
+            Color::Black => write!(f, "black"),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "w" | "white" => Ok(Color::White),
+            "b" | "black" => Ok(Color::Black),
+            _ => Err(ParseError::InvalidColor(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for PieceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.ret_type_as_char())
+    }
+}
+
+// Takes a letter in either case or the english name. Pieces that track whether
+// they have moved come back unmoved.
+impl FromStr for PieceType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "p" | "pawn" => Ok(PieceType::Pawn(false)),
+            "r" | "rook" => Ok(PieceType::Rook(false)),
+            "b" | "bishop" => Ok(PieceType::Bishop),
+            "n" | "knight" => Ok(PieceType::Knight),
+            "k" | "king" => Ok(PieceType::King(false)),
+            "q" | "queen" => Ok(PieceType::Queen),
+            _ => Err(ParseError::InvalidPieceType(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_piece_as_char())
+    }
+}
+
+// Uppercase letters are white and lowercase black, like in a fen. The unicode
+// figurines are read too.
+impl FromStr for Piece {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidPiece(s.to_string());
+        let mut chars = s.trim().chars();
+        let c = chars.next().ok_or_else(invalid)?;
+        if chars.next().is_some() {
+            return Err(invalid());
+        }
+
+        let letter = match c {
+            '\u{2654}'..='\u{2659}' => "KQRBNP".chars().nth(c as usize - 0x2654).unwrap(),
+            '\u{265A}'..='\u{265F}' => "kqrbnp".chars().nth(c as usize - 0x265A).unwrap(),
+            _ => c,
+        };
+        let piece_type = letter
+            .to_string()
+            .parse::<PieceType>()
+            .map_err(|_| invalid())?;
+        let color = if letter.is_ascii_uppercase() { Color::White } else { Color::Black };
+        Ok(Piece { piece_type, color })
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(&RenderOptions::default()))
+    }
+}
+
+// Reads the diagram print_board draws back into a board, so positions can be
+// written out as text. Highlight brackets, flipped boards and figurines are
+// understood too. A diagram has no side to move so white is to move, pawns on
+// their home rank are unmoved, and kings and rooks on their starting squares
+// keep their castling rights.
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| ParseError::InvalidDiagram(reason.to_string());
+        let mut board = [[None; 8]; 8];
+        let mut seen_ranks = [false; 8];
+
+        // the file letters under the board say which way round it is drawn
+        let flipped = s
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .is_some_and(|labels| labels.to_ascii_uppercase().starts_with('H'));
+
+        for line in s.lines().map(|line| line.trim()) {
+            let label = match line.chars().next().and_then(|c| c.to_digit(10)) {
+                Some(label) if (1..=8).contains(&label) => label as usize,
+                _ => continue,
+            };
+            let rank = 8 - label;
+            if seen_ranks[rank] {
+                return Err(invalid(&format!("rank {} appears twice", label)));
+            }
+            seen_ranks[rank] = true;
+
+            let cells: Vec<&str> = line.split('|').skip(1).take(8).collect();
+            if cells.len() != 8 {
+                return Err(invalid(&format!("rank {} does not have 8 squares", label)));
+            }
+            for (column, cell) in cells.iter().enumerate() {
+                let file = if flipped { 7 - column } else { column };
+                let content = cell.trim_matches(|c| matches!(c, ' ' | '[' | ']' | '(' | ')'));
+                if content.is_empty() {
+                    continue;
+                }
+                board[rank][file] = Some(content.parse::<Piece>()?);
+            }
+        }
+        if seen_ranks.contains(&false) {
+            return Err(invalid("expected 8 ranks"));
+        }
+
+        for (rank, row) in board.iter_mut().enumerate() {
+            for (file, square) in row.iter_mut().enumerate() {
+                if let Some(piece) = square {
+                    let (home_rank, pawn_rank) = if piece.color.is_white() { (7, 6) } else { (0, 1) };
+                    piece.piece_type = match piece.piece_type {
+                        PieceType::Pawn(_) => PieceType::Pawn(rank != pawn_rank),
+                        PieceType::King(_) => PieceType::King(!(rank == home_rank && file == 4)),
+                        PieceType::Rook(_) => PieceType::Rook(!(rank == home_rank && (file == 0 || file == 7))),
+                        other => other,
+                    };
+                }
+            }
+        }
+
+        Ok(Board {
+            board,
+            ..Default::default()
+        })
+    }
+}
+
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+// Kept back from every allocation for the time it takes the move to get from
+// the engine to the clock.
+pub const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+// moves a sudden death game is assumed to have left
+const EXPECTED_MOVES: u32 = 30;
+
+// How long a search may think about one move. Past the soft limit it finishes
+// the iteration it is on and stops, unless the best move keeps changing, and
+// at the hard limit it stops wherever it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+    instability: u32, // percent the soft limit is stretched by
+}
+
+impl TimeManager {
+    // From the mover's clock as a UCI go gives it. The hard limit always
+    // leaves the overhead on the clock, so the engine never flags.
+    pub fn new(remaining: Duration, increment: Duration, moves_to_go: Option<u32>, overhead: Duration) -> TimeManager {
+        let usable = remaining.saturating_sub(overhead);
+        let moves = moves_to_go.unwrap_or(EXPECTED_MOVES).clamp(1, 50);
+        // with the last move before the control it can use nearly all of it,
+        // otherwise never more than half in one go
+        let most = if moves == 1 { usable * 9 / 10 } else { usable / 2 };
+        let soft = (usable / moves + increment * 3 / 4).min(most);
+        TimeManager {
+            soft,
+            hard: (soft * 3).min(most),
+            instability: 100,
+        }
+    }
+
+    // exactly this long, as for go movetime
+    pub fn fixed(time: Duration, overhead: Duration) -> TimeManager {
+        let time = time.saturating_sub(overhead);
+        TimeManager {
+            soft: time,
+            hard: time,
+            instability: 100,
+        }
+    }
+
+    pub fn soft(&self) -> Duration {
+        self.soft
+    }
+
+    pub fn hard(&self) -> Duration {
+        self.hard
+    }
+
+    // Called after each iteration. A new best move stretches the soft limit,
+    // a settled one lets it shrink back.
+    pub fn iteration_done(&mut self, best_move_changed: bool) {
+        self.instability = if best_move_changed {
+            (self.instability + 50).min(250)
+        } else {
+            self.instability.saturating_sub(25).max(100)
+        };
+    }
+
+    // whether to leave it at the iteration just finished
+    pub fn should_stop(&self, elapsed: Duration) -> bool {
+        elapsed >= (self.soft * self.instability / 100).min(self.hard)
+    }
+
+    pub fn must_stop(&self, elapsed: Duration) -> bool {
+        elapsed >= self.hard
+    }
+}
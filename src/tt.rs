@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::search::{MAX_PLY, TABLEBASE_WIN};
+use crate::{decode_book_move, encode_book_move, Move};
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+// What the stored score says about the real one: exact, or only a bound
+// because the search was cut off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Entry {
+    pub key: u64,
+    pub best_move: Option<Move>,
+    pub depth: i32,
+    pub score: i32,
+    pub bound: Bound,
+}
+
+impl Entry {
+    // The move in the Polyglot encoding in the low 16 bits, 0 for none, then
+    // the score, the depth and the bound. Never 0 as the bound is never 0.
+    fn pack(&self) -> u64 {
+        let mv = self.best_move.map_or(0, |mv| encode_book_move(&mv)) as u64;
+        let score = self.score as i16 as u16 as u64;
+        let depth = self.depth.clamp(i8::MIN as i32, i8::MAX as i32) as i8 as u8 as u64;
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        mv | score << 16 | depth << 32 | bound << 40
+    }
+
+    fn unpack(key: u64, data: u64) -> Option<Entry> {
+        let bound = match data >> 40 & 3 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        let mv = data as u16;
+        Some(Entry {
+            key,
+            best_move: if mv == 0 { None } else { Some(decode_book_move(mv)) },
+            score: (data >> 16) as u16 as i16 as i32,
+            depth: (data >> 32) as u8 as i8 as i32,
+            bound,
+        })
+    }
+}
+
+// Each slot holds the entry and the entry xor its key. Two threads writing
+// the same slot at once can leave halves that do not match, and then the
+// key does not come back out and the slot reads as empty, so no lock is
+// needed.
+#[derive(Debug, Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+// Positions already searched, found by their Polyglot key and shared by all
+// the search threads. Each key has one slot and a newer or deeper search
+// replaces what was there.
+#[derive(Debug)]
+pub(crate) struct TranspositionTable {
+    slots: Vec<Slot>,
+}
+
+impl TranspositionTable {
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let count = (megabytes.max(1) << 20) / std::mem::size_of::<Slot>();
+        TranspositionTable {
+            slots: (0..count).map(|_| Slot::default()).collect(),
+        }
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.check.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn probe(&self, key: u64, ply: u32) -> Option<Entry> {
+        let mut entry = self.read(key)?;
+        entry.score = score_from_table(entry.score, ply);
+        Some(entry)
+    }
+
+    pub fn store(&self, mut entry: Entry, ply: u32) {
+        if let Some(old) = self.read(entry.key) {
+            if old.depth > entry.depth && entry.bound != Bound::Exact {
+                return;
+            }
+        }
+        entry.score = score_to_table(entry.score, ply);
+        let data = entry.pack();
+        let slot = self.slot(entry.key);
+        slot.check.store(entry.key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    fn read(&self, key: u64) -> Option<Entry> {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+        if slot.check.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        Entry::unpack(key, data)
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        &self.slots[(key % self.slots.len() as u64) as usize]
+    }
+}
+
+// Mate and tablebase scores count from the root, the table keeps them
+// counted from the position so they hold wherever it turns up again.
+fn score_to_table(score: i32, ply: u32) -> i32 {
+    if score >= TABLEBASE_WIN - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score <= -TABLEBASE_WIN + MAX_PLY as i32 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: u32) -> i32 {
+    if score >= TABLEBASE_WIN - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score <= -TABLEBASE_WIN + MAX_PLY as i32 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::eval::{evaluate_pieces, features, is_endgame, placed_pieces, PlacedPiece};
+use crate::{Board, Epd, EpdError, EvalParams};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TuneError {
+    BadPosition(EpdError),
+    NoResult(String),
+}
+
+impl fmt::Display for TuneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TuneError::BadPosition(e) => write!(f, "{}", e),
+            TuneError::NoResult(line) => write!(f, "no game result in {:?}", line),
+        }
+    }
+}
+
+impl Error for TuneError {}
+
+// Reads a position labelled with the result of the game it came from, for
+// white: a fen or EPD line ending in 1-0, 0-1 or 1/2-1/2, or in a number
+// like [0.5], or with the result as its c9 operation.
+pub fn parse_labelled(line: &str) -> Result<(Board, f64), TuneError> {
+    let line = line.trim();
+    let (rest, last) = line.rsplit_once(char::is_whitespace).unwrap_or(("", line));
+    let (position, result) = match parse_result(last) {
+        Some(result) => (rest.trim_end().trim_end_matches("c9").trim_end(), Some(result)),
+        None => (line, None),
+    };
+    let epd: Epd = position.parse().map_err(TuneError::BadPosition)?;
+    let result = result
+        .or_else(|| epd.operation("c9")?.first().and_then(|text| parse_result(text)))
+        .ok_or_else(|| TuneError::NoResult(line.to_string()))?;
+    Ok((epd.board, result))
+}
+
+fn parse_result(text: &str) -> Option<f64> {
+    let text = text.trim_matches(|c| matches!(c, '[' | ']' | '"' | ';'));
+    match text {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        // a whole number could be the move number of a fen
+        _ if text.contains('.') => text.parse().ok().filter(|result| (0.0..=1.0).contains(result)),
+        _ => None,
+    }
+}
+
+// Reads a file of labelled positions, skipping blank lines and ones starting
+// with #.
+pub fn read_labelled(text: &str) -> Vec<Result<(Board, f64), TuneError>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_labelled)
+        .collect()
+}
+
+// A position as the tuner keeps it: its pieces as the evaluation sees them,
+// which is all it looks at.
+struct Position {
+    pieces: Vec<PlacedPiece>,
+    result: f64,
+}
+
+// Texel's method: the evaluation of each position, made into an expected
+// score by a sigmoid, should be close to the result of its game. The weights
+// go down the gradient of the mean squared error. The evaluation is taken
+// as it is, without a search, so the positions should be quiet ones.
+pub struct Tuner {
+    positions: Vec<Position>,
+    pub k: f64, // the scale of the sigmoid, 1 / (1 + 10^(-k * score / 400))
+}
+
+impl Tuner {
+    pub fn new(positions: &[(Board, f64)]) -> Tuner {
+        let positions = positions
+            .iter()
+            .map(|(board, result)| Position {
+                pieces: placed_pieces(board).collect(),
+                result: *result,
+            })
+            .collect();
+        Tuner { positions, k: 1.0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn error(&self, params: &EvalParams) -> f64 {
+        self.error_of(&weights(params), self.k)
+    }
+
+    // Picks the k that gives the least error with these weights, so that
+    // tuning starts from scores that mean what the results say.
+    pub fn fit_k(&mut self, params: &EvalParams) -> f64 {
+        let weights = weights(params);
+        // the error has one low point, which a golden section search closes in on
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (0.0, 10.0);
+        while high - low > 1e-4 {
+            let left = high - ratio * (high - low);
+            let right = low + ratio * (high - low);
+            if self.error_of(&weights, left) < self.error_of(&weights, right) {
+                high = right;
+            } else {
+                low = left;
+            }
+        }
+        self.k = (low + high) / 2.0;
+        self.k
+    }
+
+    // Takes this many steps down the gradient with Adam, each weight moving
+    // by about the rate at first, and tells on_step the error after each.
+    // The weights come back rounded to whole centipawns.
+    pub fn tune(
+        &self,
+        params: &EvalParams,
+        iterations: usize,
+        rate: f64,
+        mut on_step: impl FnMut(usize, f64),
+    ) -> EvalParams {
+        let (beta1, beta2, epsilon) = (0.9, 0.999, 1e-8);
+        let mut weights = weights(params);
+        let mut momentum = vec![0.0; weights.len()];
+        let mut velocity = vec![0.0; weights.len()];
+        for step in 1..=iterations {
+            let gradient = self.gradient(&weights);
+            for i in 0..weights.len() {
+                momentum[i] = beta1 * momentum[i] + (1.0 - beta1) * gradient[i];
+                velocity[i] = beta2 * velocity[i] + (1.0 - beta2) * gradient[i] * gradient[i];
+                let momentum = momentum[i] / (1.0 - beta1.powi(step as i32));
+                let velocity = velocity[i] / (1.0 - beta2.powi(step as i32));
+                weights[i] -= rate * momentum / (velocity.sqrt() + epsilon);
+            }
+            on_step(step, self.error_of(&weights, self.k));
+        }
+        let rounded: Vec<i32> = weights.iter().map(|weight| weight.round() as i32).collect();
+        EvalParams::from_slice(&rounded).expect("there is a weight for each one there was")
+    }
+
+    fn error_of(&self, weights: &[f64], k: f64) -> f64 {
+        let total: f64 = self
+            .positions
+            .iter()
+            .map(|position| (position.result - sigmoid(evaluate(position, weights), k)).powi(2))
+            .sum();
+        total / self.positions.len().max(1) as f64
+    }
+
+    // The evaluation is a sum of weights, so each weight's share of the
+    // gradient is the count of pieces reading it, white's less black's. The
+    // step from middlegame to endgame king is taken as fixed.
+    fn gradient(&self, weights: &[f64]) -> Vec<f64> {
+        let mut gradient = vec![0.0; weights.len()];
+        let scale = self.k * 10f64.ln() / 400.0;
+        for position in &self.positions {
+            let expected = sigmoid(evaluate(position, weights), self.k);
+            let slope = 2.0 * (expected - position.result) * expected * (1.0 - expected) * scale;
+            let endgame = is_endgame(position.pieces.iter().copied(), |index| weights[index]);
+            for (index, sign) in features(position.pieces.iter().copied(), endgame) {
+                gradient[index] += f64::from(sign) * slope;
+            }
+        }
+        let count = self.positions.len().max(1) as f64;
+        gradient.iter().map(|sum| sum / count).collect()
+    }
+}
+
+fn weights(params: &EvalParams) -> Vec<f64> {
+    params.to_vec().into_iter().map(f64::from).collect()
+}
+
+fn sigmoid(score: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+// evaluate_with's score for white, on weights that need not be whole
+fn evaluate(position: &Position, weights: &[f64]) -> f64 {
+    evaluate_pieces(position.pieces.iter().copied(), |index| weights[index])
+}
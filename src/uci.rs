@@ -0,0 +1,316 @@
+use std::fs;
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{
+    mate_in, Board, EvalParams, RenderOptions, SearchLimits, SearchResult, Searcher, TimeManager, TimeSource,
+    DEFAULT_HASH_MB, DEFAULT_MOVE_OVERHEAD,
+};
+
+// The engine side of the Universal Chess Interface: commands come in a line
+// at a time and the answers go to the output. Searches run on their own
+// thread so stop and isready are answered while they go on.
+pub struct Uci<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    board: Board,
+    history: Vec<u64>, // keys of the positions before the current one
+    searcher: Option<Searcher>, // lent to the search thread while it runs
+    search: Option<JoinHandle<Searcher>>,
+    stop: Arc<AtomicBool>,
+    // Raised by stop along with the searcher's flag. The searcher lowers its
+    // own when it returns, this one stays up for an infinite search waiting
+    // to answer.
+    stopped: Arc<AtomicBool>,
+    infinite: bool, // the search running only ends when stopped
+    move_overhead: Duration,
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    pub fn new(output: W) -> Uci<W> {
+        let searcher = Searcher::new();
+        Uci {
+            output: Arc::new(Mutex::new(output)),
+            board: Board::default(),
+            history: Vec::new(),
+            stop: searcher.stop_flag(),
+            stopped: Arc::new(AtomicBool::new(false)),
+            searcher: Some(searcher),
+            search: None,
+            infinite: false,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+        }
+    }
+
+    // for tests that want the search to see a clock they control
+    pub fn set_time_source(&mut self, source: Arc<dyn TimeSource>) {
+        self.searcher().set_time_source(source);
+    }
+
+    // Reads commands until quit, which stops any search, or until the input
+    // ends, after which a search still going is let finish unless it would
+    // never end. Gives the output back at the end.
+    pub fn run<R: BufRead>(mut self, input: R) -> W {
+        for line in input.lines() {
+            match line {
+                Ok(line) if self.handle(&line) => (),
+                Ok(_) => {
+                    self.finish_search();
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+        if self.infinite {
+            self.finish_search();
+        }
+        self.searcher();
+        drop(self.searcher.take());
+        match Arc::try_unwrap(self.output) {
+            Ok(output) => output.into_inner().unwrap_or_else(|e| e.into_inner()),
+            Err(_) => unreachable!("the search thread has finished with the output"),
+        }
+    }
+
+    // Carries out one command, giving false for quit. Commands it does not
+    // know are ignored, as the protocol asks.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first().copied() {
+            Some("uci") => {
+                self.send("id name rust_chess");
+                self.send("id author the rust_chess authors");
+                self.send(&format!(
+                    "option name Hash type spin default {} min 1 max 4096",
+                    DEFAULT_HASH_MB
+                ));
+                self.send("option name Threads type spin default 1 min 1 max 256");
+                self.send("option name MultiPV type spin default 1 min 1 max 256");
+                self.send(&format!(
+                    "option name Move Overhead type spin default {} min 0 max 5000",
+                    DEFAULT_MOVE_OVERHEAD.as_millis()
+                ));
+                self.send("option name EvalFile type string default <empty>");
+                #[cfg(feature = "syzygy")]
+                self.send("option name SyzygyPath type string default <empty>");
+                self.send("uciok");
+            }
+            Some("isready") => self.send("readyok"),
+            Some("ucinewgame") => {
+                self.finish_search();
+                self.searcher().new_game();
+            }
+            Some("setoption") => self.set_option(line),
+            Some("position") => self.set_position(&words[1..]),
+            Some("go") => self.go(&words[1..]),
+            Some("stop") => self.finish_search(),
+            Some("d") => {
+                let board = self.board.render(&RenderOptions::default());
+                self.send(board.trim_end());
+                self.send(&format!("Fen: {}", self.board.to_fen()));
+            }
+            Some("quit") => return false,
+            _ => (),
+        }
+        true
+    }
+
+    fn send(&self, line: &str) {
+        send(&self.output, line);
+    }
+
+    fn searcher(&mut self) -> &mut Searcher {
+        if let Some(search) = self.search.take() {
+            self.searcher = Some(search.join().expect("the search thread panicked"));
+        }
+        self.searcher.as_mut().expect("the searcher is back once the search is joined")
+    }
+
+    // stops the search if there is one and waits for its bestmove
+    fn finish_search(&mut self) {
+        if self.search.is_some() {
+            self.stopped.store(true, Ordering::SeqCst);
+            self.stop.store(true, Ordering::SeqCst);
+            self.searcher();
+        }
+    }
+
+    // setoption name <name> value <value>, where the name can have spaces
+    fn set_option(&mut self, line: &str) {
+        let rest = match line.split_once("name") {
+            Some((_, rest)) => rest,
+            None => return,
+        };
+        let (name, value) = match rest.split_once(" value ") {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => (rest.trim(), ""),
+        };
+        self.finish_search();
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(megabytes) => self.searcher().set_hash_size(megabytes.clamp(1, 4096)),
+                Err(_) => self.send(&format!("info string Hash needs a number, not {}", value)),
+            },
+            "threads" => match value.parse::<usize>() {
+                Ok(threads) => self.searcher().set_threads(threads.clamp(1, 256)),
+                Err(_) => self.send(&format!("info string Threads needs a number, not {}", value)),
+            },
+            "multipv" => match value.parse::<usize>() {
+                Ok(lines) => self.searcher().set_multi_pv(lines.clamp(1, 256)),
+                Err(_) => self.send(&format!("info string MultiPV needs a number, not {}", value)),
+            },
+            "move overhead" => match value.parse::<u64>() {
+                Ok(millis) => self.move_overhead = Duration::from_millis(millis.min(5000)),
+                Err(_) => self.send(&format!("info string Move Overhead needs a number, not {}", value)),
+            },
+            // weights written by tune, the built in ones again when left empty
+            "evalfile" if value.is_empty() || value == "<empty>" => self.searcher().set_eval_params(EvalParams::default()),
+            "evalfile" => match fs::read_to_string(value).map_err(|e| e.to_string()).and_then(|text| {
+                text.parse::<EvalParams>().map_err(|e| e.to_string())
+            }) {
+                Ok(params) => {
+                    self.searcher().set_eval_params(params);
+                    self.send(&format!("info string evaluation weights from {}", value));
+                }
+                Err(e) => self.send(&format!("info string could not read {}: {}", value, e)),
+            },
+            #[cfg(feature = "syzygy")]
+            "syzygypath" if !value.is_empty() && value != "<empty>" => {
+                for dir in value.split(if cfg!(windows) { ';' } else { ':' }) {
+                    match crate::open_tablebases(dir) {
+                        Ok(count) => self.send(&format!("info string {} tables in {}", count, dir)),
+                        Err(e) => self.send(&format!("info string could not open {}: {}", dir, e)),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // position [startpos | fen <fen>] [moves <move>...]
+    fn set_position(&mut self, words: &[&str]) {
+        let moves_at = words.iter().position(|&word| word == "moves").unwrap_or(words.len());
+        let board = match words.first() {
+            Some(&"startpos") => Ok(Board::default()),
+            Some(&"fen") => Board::from_fen(&words[1..moves_at].join(" ")).map_err(|e| e.to_string()),
+            _ => Err("position needs startpos or fen".to_string()),
+        };
+        let mut board = match board {
+            Ok(board) => board,
+            Err(e) => return self.send(&format!("info string {}", e)),
+        };
+
+        let mut history = Vec::new();
+        for text in words.iter().skip(moves_at + 1) {
+            match board.parse_coordinates(text) {
+                Ok(mv) => {
+                    history.push(board.polyglot_key());
+                    board.apply_move(&mv);
+                }
+                Err(e) => return self.send(&format!("info string {}", e)),
+            }
+        }
+        self.finish_search();
+        self.board = board;
+        self.history = history;
+    }
+
+    // go with any of wtime btime winc binc movestogo movetime depth nodes mate infinite
+    fn go(&mut self, words: &[&str]) {
+        self.finish_search();
+        let number = |name: &str| -> Option<u64> {
+            let at = words.iter().position(|&word| word == name)?;
+            words.get(at + 1)?.parse().ok()
+        };
+        let millis = |name: &str| number(name).map(Duration::from_millis);
+        let white = self.board.side_to_move().is_white();
+
+        let mut limits = SearchLimits {
+            depth: number("depth").map(|depth| depth as u32),
+            nodes: number("nodes"),
+            ..Default::default()
+        };
+        if let Some(moves) = number("mate") {
+            limits.depth = Some(moves as u32 * 2);
+        }
+        let (time, increment) = if white { ("wtime", "winc") } else { ("btime", "binc") };
+        if let Some(movetime) = millis("movetime") {
+            limits.time_manager = Some(TimeManager::fixed(movetime, self.move_overhead));
+        } else if let Some(remaining) = millis(time) {
+            let increment = millis(increment).unwrap_or_default();
+            let moves_to_go = number("movestogo").map(|moves| moves as u32);
+            limits.time_manager = Some(TimeManager::new(remaining, increment, moves_to_go, self.move_overhead));
+        }
+        let infinite = words.contains(&"infinite");
+        self.infinite = infinite;
+
+        let mut searcher = match self.searcher.take() {
+            Some(searcher) => searcher,
+            None => return,
+        };
+        let output = self.output.clone();
+        let root = self.board.clone();
+        let multi_pv = searcher.multi_pv() > 1;
+        searcher.set_reporter(Some(Arc::new(move |result: &SearchResult| {
+            send(&output, &info_line(&root, result, multi_pv));
+        })));
+
+        let output = self.output.clone();
+        let stopped = self.stopped.clone();
+        let board = self.board.clone();
+        let history = self.history.clone();
+        self.stop.store(false, Ordering::SeqCst);
+        stopped.store(false, Ordering::SeqCst);
+        self.search = Some(thread::spawn(move || {
+            let result = searcher.search_with_history(&board, &history, limits);
+            searcher.set_reporter(None);
+            // an infinite search only answers once it is told to stop
+            while infinite && !stopped.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            let best = match result.best_move {
+                Some(mv) => board.move_to_coordinates(&mv),
+                None => "0000".to_string(),
+            };
+            send(&output, &format!("bestmove {}", best));
+            searcher
+        }));
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, line: &str) {
+    let mut output = output.lock().unwrap_or_else(|e| e.into_inner());
+    // a gui that has gone away is not the engine's problem
+    let _ = writeln!(output, "{}", line);
+    let _ = output.flush();
+}
+
+// info depth 5 score cp 31 nodes 12000 nps 240000 time 50 pv e2e4 e7e5, with
+// multipv 2 after the depth when more than one line is searched
+pub(crate) fn info_line(board: &Board, result: &SearchResult, multi_pv: bool) -> String {
+    let score = match mate_in(result.score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+    let rank = if multi_pv { format!(" multipv {}", result.rank) } else { String::new() };
+    let millis = result.time.as_millis() as u64;
+    let nps = result.nodes * 1000 / millis.max(1);
+    let mut board = board.clone();
+    let mut pv = Vec::new();
+    for mv in &result.pv {
+        pv.push(board.move_to_coordinates(mv));
+        board.apply_move(mv);
+    }
+    format!(
+        "info depth {}{} score {} nodes {} nps {} time {} pv {}",
+        result.depth,
+        rank,
+        score,
+        result.nodes,
+        nps,
+        millis,
+        pv.join(" ")
+    )
+}
@@ -0,0 +1,506 @@
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    array_notation_to_chess_notation, Board, Color, Game, Move, Player, PlayerMove, SearchResult, MATE, STARTING_FEN,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciError {
+    Io(String),
+    Timeout(String), // what the engine was waited on for
+    Exited,
+    UnknownOption(String),
+    BadOptionValue { name: String, value: String },
+    BadMove(String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciError::Io(e) => write!(f, "{}", e),
+            UciError::Timeout(waiting) => write!(f, "the engine took too long to answer {}", waiting),
+            UciError::Exited => write!(f, "the engine has exited"),
+            UciError::UnknownOption(name) => write!(f, "the engine has no option {:?}", name),
+            UciError::BadOptionValue { name, value } => write!(f, "{:?} is not a value for option {}", value, name),
+            UciError::BadMove(text) => write!(f, "the engine played {:?}, which is not a legal move", text),
+        }
+    }
+}
+
+impl Error for UciError {}
+
+// An option the engine said it has, as in
+// option name Hash type spin default 16 min 1 max 1024
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UciOption {
+    pub name: String,
+    pub kind: String, // check, spin, combo, button or string
+    pub default: Option<String>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub vars: Vec<String>, // the choices of a combo
+}
+
+impl UciOption {
+    fn parse(line: &str) -> Option<UciOption> {
+        let mut option = UciOption::default();
+        let mut field = "";
+        let mut value = Vec::new();
+        // the name runs up to type, as it may have spaces and these words in it
+        fn set(option: &mut UciOption, field: &str, value: &mut Vec<&str>) {
+            let text = value.join(" ");
+            value.clear();
+            match field {
+                "name" => option.name = text,
+                "type" => option.kind = text,
+                "default" => option.default = Some(text),
+                "min" => option.min = text.parse().ok(),
+                "max" => option.max = text.parse().ok(),
+                "var" => option.vars.push(text),
+                _ => (),
+            }
+        }
+        for word in line.split_whitespace().skip(1) {
+            if matches!(word, "name" | "type" | "default" | "min" | "max" | "var") && (field != "name" || word == "type")
+            {
+                set(&mut option, field, &mut value);
+                field = word;
+            } else {
+                value.push(word);
+            }
+        }
+        set(&mut option, field, &mut value);
+        if option.name.is_empty() {
+            return None;
+        }
+        Some(option)
+    }
+
+    fn accepts(&self, value: &str) -> bool {
+        match self.kind.as_str() {
+            "check" => matches!(value, "true" | "false"),
+            "spin" => value
+                .parse::<i64>()
+                .is_ok_and(|n| self.min.is_none_or(|min| n >= min) && self.max.is_none_or(|max| n <= max)),
+            "combo" => self.vars.iter().any(|var| var.eq_ignore_ascii_case(value)),
+            "button" => value.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+// What to send with go. Times are for the engine's clocks, movetime a fixed
+// time for the move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GoLimits {
+    pub white_time: Option<Duration>,
+    pub black_time: Option<Duration>,
+    pub white_increment: Option<Duration>,
+    pub black_increment: Option<Duration>,
+    pub moves_to_go: Option<u32>,
+    pub move_time: Option<Duration>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u32>,
+}
+
+impl GoLimits {
+    pub fn depth(depth: u32) -> GoLimits {
+        GoLimits { depth: Some(depth), ..Default::default() }
+    }
+
+    pub fn move_time(time: Duration) -> GoLimits {
+        GoLimits { move_time: Some(time), ..Default::default() }
+    }
+
+    fn command(&self) -> String {
+        let mut command = "go".to_string();
+        let millis = |time: Duration| time.as_millis().to_string();
+        let words = [
+            ("wtime", self.white_time.map(millis)),
+            ("btime", self.black_time.map(millis)),
+            ("winc", self.white_increment.map(millis)),
+            ("binc", self.black_increment.map(millis)),
+            ("movestogo", self.moves_to_go.map(|moves| moves.to_string())),
+            ("movetime", self.move_time.map(millis)),
+            ("depth", self.depth.map(|depth| depth.to_string())),
+            ("nodes", self.nodes.map(|nodes| nodes.to_string())),
+            ("mate", self.mate.map(|moves| moves.to_string())),
+        ];
+        for (name, value) in words {
+            if let Some(value) = value {
+                command.push_str(&format!(" {} {}", name, value));
+            }
+        }
+        command
+    }
+
+    // the longest the search should take, when there is a time limit
+    fn time_for(&self, side: Color) -> Option<Duration> {
+        let clock = match side {
+            Color::White => self.white_time,
+            Color::Black => self.black_time,
+        };
+        match (self.move_time, clock) {
+            (Some(move_time), Some(clock)) => Some(move_time.min(clock)),
+            (move_time, clock) => move_time.or(clock),
+        }
+    }
+}
+
+// What the engine said during a search: the last line of info for each
+// multipv rank, best first, any info strings, and its move.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EngineOutput {
+    pub best_move: Option<Move>,
+    pub ponder: Option<Move>,
+    pub lines: Vec<SearchResult>,
+    pub strings: Vec<String>,
+}
+
+// An engine in another process that speaks UCI. Lines it writes are read on
+// a thread of their own, so nothing waits on the engine longer than the
+// timeout. The engine is told to quit when this is dropped.
+#[derive(Debug)]
+pub struct UciEngine {
+    child: Child,
+    input: ChildStdin,
+    output: Receiver<String>,
+    name: String,
+    pub author: String,
+    pub options: Vec<UciOption>,
+    // how long to wait for answers that should come at once, and past the
+    // time a search was given before stopping it
+    pub timeout: Duration,
+    // how long a search with no time in its limits, by depth or nodes, may
+    // go on before it is stopped
+    pub max_search_time: Duration,
+    // used when a game has no clock
+    pub limits: GoLimits,
+    chess960: bool, // whether UCI_Chess960 has been turned on
+}
+
+impl UciEngine {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+    pub const DEFAULT_MAX_SEARCH_TIME: Duration = Duration::from_secs(300);
+
+    // Starts the engine and waits for it to list its options.
+    pub fn start<S: AsRef<OsStr>>(program: S, args: &[&str]) -> Result<UciEngine, UciError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| UciError::Io(format!("could not start the engine: {}", e)))?;
+        let input = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let sent = line.is_ok_and(|line| sender.send(line).is_ok());
+                if !sent {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = UciEngine {
+            child,
+            input,
+            output,
+            name: String::new(),
+            author: String::new(),
+            options: Vec::new(),
+            timeout: UciEngine::DEFAULT_TIMEOUT,
+            max_search_time: UciEngine::DEFAULT_MAX_SEARCH_TIME,
+            limits: GoLimits::depth(8),
+            chess960: false,
+        };
+        engine.send("uci")?;
+        let deadline = Instant::now() + engine.timeout;
+        loop {
+            let line = engine.read_line(deadline, "uci")?;
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("id"), Some("name")) => engine.name = words.collect::<Vec<_>>().join(" "),
+                (Some("id"), Some("author")) => engine.author = words.collect::<Vec<_>>().join(" "),
+                (Some("option"), _) => engine.options.extend(UciOption::parse(&line)),
+                (Some("uciok"), _) => break,
+                _ => (),
+            }
+        }
+        if engine.name.is_empty() {
+            engine.name = "engine".to_string();
+        }
+        Ok(engine)
+    }
+
+    // the name it plays under, the one it gave unless changed
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    // Sets an option the engine listed, checking the value against what it
+    // said it takes.
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), UciError> {
+        let option = self
+            .options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| UciError::UnknownOption(name.to_string()))?;
+        if !option.accepts(value) {
+            return Err(UciError::BadOptionValue {
+                name: option.name.clone(),
+                value: value.to_string(),
+            });
+        }
+        let command = if option.kind == "button" {
+            format!("setoption name {}", option.name)
+        } else {
+            format!("setoption name {} value {}", option.name, value)
+        };
+        self.send(&command)
+    }
+
+    // waits until the engine has dealt with everything sent so far
+    pub fn is_ready(&mut self) -> Result<(), UciError> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.timeout;
+        while self.read_line(deadline, "isready")? != "readyok" {}
+        Ok(())
+    }
+
+    pub fn new_game(&mut self) -> Result<(), UciError> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    // The position after the moves from the start, sent as the start and the
+    // moves. A chess960 start turns on UCI_Chess960, and castling is then sent
+    // as the king taking its rook, which fails for an engine without it.
+    pub fn set_position(&mut self, start: &Board, moves: &[Move]) -> Result<(), UciError> {
+        let chess960 = start.is_chess960();
+        if chess960 != self.chess960 {
+            self.set_option("UCI_Chess960", if chess960 { "true" } else { "false" })?;
+            self.chess960 = chess960;
+        }
+        let fen = start.to_fen();
+        let mut command = if fen == STARTING_FEN {
+            "position startpos".to_string()
+        } else {
+            format!("position fen {}", fen)
+        };
+        if !moves.is_empty() {
+            command.push_str(" moves");
+            let mut board = start.clone();
+            for mv in moves {
+                command.push(' ');
+                if chess960 && board.is_castling(mv) {
+                    command.push_str(&array_notation_to_chess_notation(mv.from));
+                    command.push_str(&array_notation_to_chess_notation(mv.to));
+                } else {
+                    command.push_str(&board.move_to_coordinates(mv));
+                }
+                board.apply_move(mv);
+            }
+        }
+        self.send(&command)
+    }
+
+    // Searches the position after the moves and waits for the engine's
+    // move. Past the time the limits give it, plus the timeout, or past
+    // max_search_time when they give none, the engine is told to stop, and
+    // after another timeout given up on.
+    pub fn go(&mut self, start: &Board, moves: &[Move], limits: &GoLimits) -> Result<EngineOutput, UciError> {
+        let mut board = start.clone();
+        for mv in moves {
+            board.apply_move(mv);
+        }
+        self.set_position(start, moves)?;
+        self.send(&limits.command())?;
+
+        let mut deadline = Instant::now()
+            + limits
+                .time_for(board.side_to_move())
+                .map_or(self.max_search_time, |time| time + self.timeout);
+        let mut stopped = false;
+        let mut output = EngineOutput::default();
+        loop {
+            let line = match self.read_line(deadline, "go") {
+                Err(UciError::Timeout(_)) if !stopped => {
+                    self.send("stop")?;
+                    stopped = true;
+                    deadline = Instant::now() + self.timeout;
+                    continue;
+                }
+                line => line?,
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.first().copied() {
+                Some("info") if words.get(1) == Some(&"string") => output.strings.push(words[2..].join(" ")),
+                Some("info") => {
+                    if let Some(info) = parse_info(&board, &words[1..]) {
+                        let rank = info.rank.max(1);
+                        output.lines.retain(|line| line.rank.max(1) != rank);
+                        output.lines.push(info);
+                        output.lines.sort_by_key(|line| line.rank);
+                    }
+                }
+                Some("bestmove") => {
+                    let text = words.get(1).copied().unwrap_or("0000");
+                    if text != "0000" && text != "(none)" {
+                        let mv = board
+                            .parse_coordinates(text)
+                            .map_err(|_| UciError::BadMove(text.to_string()))?;
+                        output.best_move = Some(mv);
+                        if let (Some(&"ponder"), Some(ponder)) = (words.get(2), words.get(3)) {
+                            let mut after = board.clone();
+                            after.apply_move(&mv);
+                            output.ponder = after.parse_coordinates(ponder).ok();
+                        }
+                    }
+                    return Ok(output);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // asks the engine to quit and waits a moment for it, then ends it
+    pub fn quit(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), UciError> {
+        writeln!(self.input, "{}", line)
+            .and_then(|_| self.input.flush())
+            .map_err(|_| UciError::Exited)
+    }
+
+    fn read_line(&self, deadline: Instant, waiting: &str) -> Result<String, UciError> {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match self.output.recv_timeout(left) {
+            Ok(line) => Ok(line.trim().to_string()),
+            Err(RecvTimeoutError::Timeout) => Err(UciError::Timeout(waiting.to_string())),
+            Err(RecvTimeoutError::Disconnected) => Err(UciError::Exited),
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            self.shut_down();
+        }
+    }
+}
+
+// depth 12 multipv 1 score cp 31 nodes 120000 time 500 pv e2e4 e7e5, with
+// the score turned into this crate's, so mates count down from MATE
+fn parse_info(board: &Board, words: &[&str]) -> Option<SearchResult> {
+    let mut result = SearchResult::default();
+    let mut scored = false;
+    let mut i = 0;
+    while i < words.len() {
+        let next = words.get(i + 1).copied().unwrap_or("");
+        match words[i] {
+            "depth" => result.depth = next.parse().ok()?,
+            "multipv" => result.rank = next.parse().ok()?,
+            "nodes" => result.nodes = next.parse().ok()?,
+            "time" => result.time = Duration::from_millis(next.parse().ok()?),
+            "score" => {
+                let value: i32 = words.get(i + 2)?.parse().ok()?;
+                result.score = match next {
+                    "cp" => value,
+                    "mate" if value > 0 => MATE - (2 * value - 1),
+                    "mate" => -MATE - 2 * value,
+                    _ => return None,
+                };
+                scored = true;
+                i += 1;
+            }
+            "pv" => {
+                let mut board = board.clone();
+                for text in &words[i + 1..] {
+                    match board.parse_coordinates(text) {
+                        Ok(mv) => {
+                            board.apply_move(&mv);
+                            result.pv.push(mv);
+                        }
+                        Err(_) => break,
+                    }
+                }
+                break;
+            }
+            "string" => return None,
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    if !scored {
+        return None;
+    }
+    result.best_move = result.pv.first().copied();
+    Some(result)
+}
+
+// An engine plays on the game's clock when it has one, and to its limits
+// otherwise.
+impl Player for UciEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self) {
+        // an engine that fails here fails again on its first move, which
+        // loses the game
+        let _ = UciEngine::new_game(self);
+    }
+
+    fn play(&mut self, game: &Game) -> Result<PlayerMove, String> {
+        let limits = match &game.clock {
+            Some(clock) => GoLimits {
+                white_time: Some(clock.remaining(Color::White)),
+                black_time: Some(clock.remaining(Color::Black)),
+                white_increment: Some(clock.increment(Color::White)),
+                black_increment: Some(clock.increment(Color::Black)),
+                moves_to_go: clock.moves_to_go(game.side_to_move()),
+                ..Default::default()
+            },
+            None => self.limits,
+        };
+        let moves: Vec<Move> = game.moves().iter().map(|record| record.mv).collect();
+        let output = self.go(game.start(), &moves, &limits).map_err(|e| e.to_string())?;
+        match output.best_move {
+            Some(mv) => Ok(PlayerMove {
+                mv,
+                score: output.lines.first().map(|line| line.score),
+            }),
+            None => Err("the engine had no move".to_string()),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{array_notation_to_chess_notation, build_piece, Board, Color, Piece, PieceType};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionProblem {
+    KingCount(Color, usize),
+    TooManyPawns(Color, usize),
+    TooManyPieces(Color, usize),
+    PawnOnBackRank((usize, usize)),
+    PawnMovedFlag((usize, usize)),     // moved flag disagrees with being on the home rank
+    CastlingKingMoved((usize, usize)), // unmoved king that is not on its back rank
+    CastlingRookMoved((usize, usize)), // unmoved rook that is not on its back rank
+    CastlingRookTwice((usize, usize)), // second unmoved rook on the same side of its king
+    OpponentInCheck,
+    ImpossibleCheck(usize), // number of pieces giving check
+    BadEnPessant((usize, usize)),
+}
+
+impl fmt::Display for PositionProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let square = |pos: &(usize, usize)| array_notation_to_chess_notation(*pos);
+        match self {
+            PositionProblem::KingCount(color, count) => {
+                write!(f, "{} has {} kings instead of one", color, count)
+            }
+            PositionProblem::TooManyPawns(color, count) => write!(f, "{} has {} pawns", color, count),
+            PositionProblem::TooManyPieces(color, count) => {
+                write!(f, "{} has {} pieces, more than promotions allow", color, count)
+            }
+            PositionProblem::PawnOnBackRank(pos) => write!(f, "pawn on {} is on a back rank", square(pos)),
+            PositionProblem::PawnMovedFlag(pos) => {
+                write!(f, "pawn on {} disagrees with its home rank about having moved", square(pos))
+            }
+            PositionProblem::CastlingKingMoved(pos) => {
+                write!(f, "king on {} can castle but is not on its back rank", square(pos))
+            }
+            PositionProblem::CastlingRookMoved(pos) => {
+                write!(f, "rook on {} can castle but is not on its back rank", square(pos))
+            }
+            PositionProblem::CastlingRookTwice(pos) => {
+                write!(f, "rook on {} can castle on the same side as another rook", square(pos))
+            }
+            PositionProblem::OpponentInCheck => write!(f, "the side not to move is in check"),
+            PositionProblem::ImpossibleCheck(count) => {
+                write!(f, "the king is checked by {} pieces in a way no move could cause", count)
+            }
+            PositionProblem::BadEnPessant(pos) => {
+                write!(f, "{} cannot be an en passant square here", square(pos))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionError {
+    pub problems: Vec<PositionProblem>,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems: Vec<String> = self.problems.iter().map(|problem| problem.to_string()).collect();
+        write!(f, "illegal position: {}", problems.join(", "))
+    }
+}
+
+impl Error for PositionError {}
+
+impl Board {
+    // Checks the position could come up in a game, collecting every problem
+    // rather than stopping at the first.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        let mut problems = Vec::new();
+
+        for color in [Color::White, Color::Black] {
+            let pieces: Vec<Piece> = self
+                .board
+                .iter()
+                .flatten()
+                .flatten()
+                .copied()
+                .filter(|piece| piece.color == color)
+                .collect();
+            let count = |wanted: fn(PieceType) -> bool| pieces.iter().filter(|piece| wanted(piece.piece_type)).count();
+
+            let kings = count(|t| matches!(t, PieceType::King(_)));
+            if kings != 1 {
+                problems.push(PositionProblem::KingCount(color, kings));
+            }
+            let pawns = count(|t| matches!(t, PieceType::Pawn(_)));
+            if pawns > 8 {
+                problems.push(PositionProblem::TooManyPawns(color, pawns));
+            }
+            // every piece past the starting set has to be a promoted pawn
+            let extra = count(|t| t == PieceType::Queen).saturating_sub(1)
+                + count(|t| matches!(t, PieceType::Rook(_))).saturating_sub(2)
+                + count(|t| t == PieceType::Bishop).saturating_sub(2)
+                + count(|t| t == PieceType::Knight).saturating_sub(2);
+            if pieces.len() > 16 || pawns + extra > 8 {
+                problems.push(PositionProblem::TooManyPieces(color, pieces.len()));
+            }
+        }
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let piece = match self.board[rank][file] {
+                    Some(piece) => piece,
+                    None => continue,
+                };
+                let (back_rank, pawn_rank) = if piece.color.is_white() { (7, 6) } else { (0, 1) };
+                match piece.piece_type {
+                    PieceType::Pawn(_) if rank == 0 || rank == 7 => {
+                        problems.push(PositionProblem::PawnOnBackRank((rank, file)));
+                    }
+                    PieceType::Pawn(moved) if moved == (rank == pawn_rank) => {
+                        problems.push(PositionProblem::PawnMovedFlag((rank, file)));
+                    }
+                    PieceType::King(false) if rank != back_rank => {
+                        problems.push(PositionProblem::CastlingKingMoved((rank, file)));
+                    }
+                    PieceType::Rook(false) if rank != back_rank => {
+                        problems.push(PositionProblem::CastlingRookMoved((rank, file)));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        // Each castling right names one rook, so an unmoved king can have at
+        // most one unmoved rook on either side of it.
+        for (color, rank) in [(Color::White, 7), (Color::Black, 0)] {
+            let king = build_piece(PieceType::King(false), color);
+            let king_file = match (0..8).find(|&file| self.board[rank][file] == king) {
+                Some(file) => file,
+                None => continue,
+            };
+            let rook = build_piece(PieceType::Rook(false), color);
+            let short = (king_file + 1..8).rev().filter(|&file| self.board[rank][file] == rook);
+            let long = (0..king_file).filter(|&file| self.board[rank][file] == rook);
+            for file in short.skip(1).chain(long.skip(1)) {
+                problems.push(PositionProblem::CastlingRookTwice((rank, file)));
+            }
+        }
+
+        let to_move = self.side_to_move();
+        if self.is_in_check(to_move.opposite()) {
+            problems.push(PositionProblem::OpponentInCheck);
+        }
+
+        // A move can give at most two checks at once, and then one of them has
+        // to come from a rook, bishop or queen the moving piece got out of the way.
+        if let Some(king) = self.king_position(to_move) {
+            let checkers = self.attackers(king, to_move.opposite());
+            let sliders = checkers
+                .iter()
+                .filter(|&&(rank, file)| {
+                    matches!(
+                        self.board[rank][file].map(|piece| piece.piece_type),
+                        Some(PieceType::Queen | PieceType::Bishop | PieceType::Rook(_))
+                    )
+                })
+                .count();
+            if checkers.len() > 2 || (checkers.len() == 2 && sliders == 0) {
+                problems.push(PositionProblem::ImpossibleCheck(checkers.len()));
+            }
+        }
+
+        if let Some((rank, file)) = self.en_pessant_square {
+            // the pawn that just moved two has to be in front of the square with nothing behind it
+            let (target_rank, pawn_rank, start_rank, pawn_color) = match to_move {
+                Color::White => (2, 3, 1, Color::Black),
+                Color::Black => (5, 4, 6, Color::White),
+            };
+            let pawn_there = matches!(
+                self.board[pawn_rank][file],
+                Some(Piece { piece_type: PieceType::Pawn(_), color }) if color == pawn_color
+            );
+            if rank != target_rank
+                || !pawn_there
+                || self.board[rank][file].is_some()
+                || self.board[start_rank][file].is_some()
+            {
+                problems.push(PositionProblem::BadEnPessant((rank, file)));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(PositionError { problems })
+        }
+    }
+}
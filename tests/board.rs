@@ -0,0 +1,47 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use rust_chess::*;
+
+fn hash(board: &Board) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    board.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn make_move_copy_leaves_the_board_alone() {
+    let board = Board::default();
+    let before = board.to_fen();
+    let after = board.make_move_copy_chess_notation("e2", "e4");
+    assert_eq!(board.to_fen(), before);
+    assert_eq!(after.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_ne!(after, board);
+
+    // an illegal move gives back an unchanged copy
+    let same = board.make_move_copy_chess_notation("e2", "e5");
+    assert_eq!(same, board);
+    assert_eq!(board.make_move_copy_chess_notation("z9", "e4"), board);
+}
+
+#[test]
+fn make_move_copy_promotes_without_asking() {
+    let board = Board::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let promoted = board.make_move_copy_chess_notation("e7", "e8");
+    assert_eq!(promoted.to_fen(), "4Q3/6k1/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(board.to_fen(), "8/4P1k1/8/8/8/8/8/4K3 w - - 0 1");
+}
+
+#[test]
+fn equality_leaves_out_the_move_counters() {
+    let board = Board::default();
+    let mut later = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 9").unwrap();
+    assert_eq!(later, board);
+    assert_eq!(hash(&later), hash(&board));
+
+    later.turn = 1;
+    assert_ne!(later, board);
+    let copy = board.clone();
+    assert_eq!(copy, board);
+    assert_eq!(hash(&copy), hash(&board));
+}
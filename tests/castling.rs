@@ -0,0 +1,81 @@
+use rust_chess::*;
+
+fn castles(board: &Board) -> Vec<String> {
+    let mut moves: Vec<String> = board
+        .legal_moves()
+        .iter()
+        .filter(|mv| board.is_castling(mv))
+        .map(|mv| board.move_to_san(mv))
+        .collect();
+    moves.sort();
+    moves
+}
+
+fn after(fen: &str, moves: &[&str]) -> Board {
+    let mut board = Board::from_fen(fen).unwrap();
+    for text in moves {
+        let mv = board.parse_move(text).unwrap();
+        assert!(board.play_move(&mv), "{}", text);
+    }
+    board
+}
+
+#[test]
+fn castles_both_ways_when_free() {
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(castles(&board), ["O-O", "O-O-O"]);
+    assert_eq!(after(&board.to_fen(), &["O-O"]).to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    assert_eq!(after(&board.to_fen(), &["O-O-O"]).to_fen(), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1");
+}
+
+#[test]
+fn no_castling_out_of_check() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1").unwrap();
+    assert!(castles(&board).is_empty());
+}
+
+#[test]
+fn no_castling_through_or_into_an_attacked_square() {
+    // the rook on f8 covers f1, which the king crosses going short
+    let through = Board::from_fen("4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(castles(&through), ["O-O-O"]);
+    // the rook on c8 covers c1, where the king lands going long
+    let into = Board::from_fen("2r1k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(castles(&into), ["O-O"]);
+    // b1 is crossed only by the rook, so an attack there does not matter
+    let rook_path = Board::from_fen("1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(castles(&rook_path), ["O-O", "O-O-O"]);
+}
+
+#[test]
+fn no_castling_with_a_rook_that_has_moved() {
+    let board = after("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &["Rh2", "Rh7", "Rh1", "Rh8"]);
+    assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w Qq - 4 3");
+    assert_eq!(castles(&board), ["O-O-O"]);
+
+    let king_moved = after("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &["Kd1", "Kd8", "Ke1", "Ke8"]);
+    assert!(castles(&king_moved).is_empty());
+}
+
+#[test]
+fn chess960_castling_lands_on_the_usual_files() {
+    // king on b1 between rooks on a1 and g1
+    let board = Board::from_fen("rk4r1/pppppppp/8/8/8/8/PPPPPPPP/RK4R1 w GAga - 0 1").unwrap();
+    assert_eq!(castles(&board), ["O-O", "O-O-O"]);
+    assert_eq!(after(&board.to_fen(), &["O-O"]).to_shredder_fen(), "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/R4RK1 b ga - 1 1");
+    assert_eq!(after(&board.to_fen(), &["O-O-O"]).to_shredder_fen(), "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/2KR2R1 b ga - 1 1");
+
+    // the king crosses c1 to g1 going short, and any other piece there stops it
+    let blocked = Board::from_fen("rk4r1/pppppppp/8/8/8/8/PPPPPPPP/RK2B1R1 w GAga - 0 1").unwrap();
+    assert_eq!(castles(&blocked), ["O-O-O"]);
+    // a bishop on h3 covers f1 through the gap
+    let attacked = Board::from_fen("rk4r1/pppppppp/8/8/8/7b/PPPPPP1P/RK4R1 w GAga - 0 1").unwrap();
+    assert_eq!(castles(&attacked), ["O-O-O"]);
+}
+
+#[test]
+fn chess960_start_positions() {
+    assert_eq!(Board::chess960(518).unwrap().to_fen(), STARTING_FEN);
+    assert_eq!(Board::chess960(0).unwrap().to_shredder_fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1");
+    assert!(Board::chess960(960).is_none());
+}
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_chess::*;
+
+fn secs(n: u64) -> Duration {
+    Duration::from_secs(n)
+}
+
+fn clock(control: &str) -> (Clock, ManualTime) {
+    let time = ManualTime::new();
+    let clock = Clock::with_time_source(control.parse().unwrap(), Arc::new(time.clone()));
+    (clock, time)
+}
+
+// each side in turn thinks for the given number of seconds and presses
+fn play(clock: &mut Clock, time: &ManualTime, thinking: &[u64]) {
+    for &n in thinking {
+        time.advance(secs(n));
+        assert!(clock.press());
+    }
+}
+
+#[test]
+fn sudden_death() {
+    let (mut clock, time) = clock("300");
+    assert_eq!(clock.remaining(Color::White), secs(300));
+    assert_eq!(clock.moves_to_go(Color::White), None);
+
+    clock.start(Color::White);
+    time.advance(secs(10));
+    // the running side's time goes down before it presses
+    assert_eq!(clock.remaining(Color::White), secs(290));
+    assert!(clock.press());
+    assert_eq!(clock.running(), Some(Color::Black));
+    play(&mut clock, &time, &[20, 5]);
+    assert_eq!(clock.remaining(Color::White), secs(285));
+    assert_eq!(clock.remaining(Color::Black), secs(280));
+    assert_eq!(clock.to_string(), " white 4:45   *black 4:40");
+}
+
+#[test]
+fn fischer_increment() {
+    let (mut clock, time) = clock("60+2");
+    assert_eq!(clock.increment(Color::White), secs(2));
+    clock.start(Color::White);
+    play(&mut clock, &time, &[5, 1, 0]);
+    assert_eq!(clock.remaining(Color::White), secs(60 - 5 + 2 + 2));
+    assert_eq!(clock.remaining(Color::Black), secs(60 - 1 + 2));
+}
+
+#[test]
+fn bronstein_delay() {
+    let (mut clock, time) = clock("60b3");
+    clock.start(Color::White);
+    // the time used comes back up to the delay, so a quick move costs nothing
+    play(&mut clock, &time, &[2, 10]);
+    assert_eq!(clock.remaining(Color::White), secs(60));
+    assert_eq!(clock.remaining(Color::Black), secs(53));
+}
+
+#[test]
+fn simple_delay() {
+    let (mut clock, time) = clock("60d3");
+    clock.start(Color::White);
+    time.advance(secs(2));
+    // nothing is taken off while the delay lasts
+    assert_eq!(clock.remaining(Color::White), secs(60));
+    assert!(clock.press());
+    play(&mut clock, &time, &[10]);
+    assert_eq!(clock.remaining(Color::White), secs(60));
+    assert_eq!(clock.remaining(Color::Black), secs(53));
+}
+
+#[test]
+fn multi_period_rollover_at_move_40() {
+    let (mut clock, time) = clock("40/5400:1800");
+    clock.start(Color::White);
+    for _ in 0..39 {
+        play(&mut clock, &time, &[60, 60]);
+    }
+    assert_eq!(clock.moves_to_go(Color::White), Some(1));
+    assert_eq!(clock.remaining(Color::White), secs(5400 - 39 * 60));
+
+    // white's 40th move adds the second period's time and ends the move count
+    play(&mut clock, &time, &[60]);
+    assert_eq!(clock.remaining(Color::White), secs(5400 - 40 * 60 + 1800));
+    assert_eq!(clock.moves_to_go(Color::White), None);
+    assert_eq!(clock.moves_to_go(Color::Black), Some(1));
+    play(&mut clock, &time, &[60]);
+    assert_eq!(clock.remaining(Color::Black), secs(5400 - 40 * 60 + 1800));
+}
+
+#[test]
+fn repeating_last_period() {
+    let (mut clock, time) = clock("2/100:1/10");
+    clock.start(Color::White);
+    play(&mut clock, &time, &[1, 1, 1, 1]);
+    assert_eq!(clock.remaining(Color::White), secs(108));
+    assert_eq!(clock.moves_to_go(Color::White), Some(1));
+    play(&mut clock, &time, &[1, 1]);
+    assert_eq!(clock.remaining(Color::White), secs(117));
+}
+
+#[test]
+fn flag_falls_while_running_or_when_pressed() {
+    let (mut clock, time) = clock("10");
+    clock.start(Color::White);
+    time.advance(secs(11));
+    // seen before the press
+    assert_eq!(clock.flagged(), Some(Color::White));
+    assert!(!clock.press());
+    assert_eq!(clock.running(), None);
+    assert_eq!(clock.remaining(Color::White), Duration::ZERO);
+    // a flagged clock stays stopped
+    clock.start(Color::Black);
+    assert_eq!(clock.running(), None);
+    assert_eq!(clock.to_string(), " white 0:00.0 (flag)    black 0:10.0");
+}
+
+#[test]
+fn flag_falls_while_paused() {
+    let (mut clock, time) = clock("10");
+    clock.start(Color::Black);
+    time.advance(secs(4));
+    clock.pause();
+    // time does not run while paused
+    time.advance(secs(100));
+    assert_eq!(clock.remaining(Color::Black), secs(6));
+    assert_eq!(clock.flagged(), None);
+
+    clock.start(Color::Black);
+    time.advance(secs(7));
+    clock.pause();
+    assert_eq!(clock.flagged(), Some(Color::Black));
+    assert_eq!(clock.remaining(Color::Black), Duration::ZERO);
+}
+
+#[test]
+fn restore_puts_the_clock_back() {
+    let (mut clock, time) = clock("40/5400:1800");
+    clock.restore(Color::White, secs(700), 45);
+    clock.restore(Color::Black, secs(30), 39);
+    assert_eq!(clock.remaining(Color::White), secs(700));
+    assert_eq!(clock.moves_to_go(Color::White), None);
+    assert_eq!(clock.moves_to_go(Color::Black), Some(1));
+
+    // black's next move still makes the time control
+    clock.start(Color::Black);
+    play(&mut clock, &time, &[10]);
+    assert_eq!(clock.remaining(Color::Black), secs(20 + 1800));
+
+    let (mut clock, _) = self::clock("300");
+    clock.restore(Color::Black, Duration::ZERO, 10);
+    assert_eq!(clock.flagged(), Some(Color::Black));
+}
+
+#[test]
+fn time_controls_round_trip() {
+    for text in ["300", "60+2", "40/5400+30:1800+30", "40/7200:20/3600:900", "60d3", "60b3", "0.5+0.1", "90+30d5"] {
+        let control: TimeControl = text.parse().unwrap();
+        assert_eq!(control.to_string(), text);
+        assert_eq!(control.to_string().parse::<TimeControl>().unwrap(), control);
+    }
+    assert_eq!(
+        "40/5400:1800".parse::<TimeControl>().unwrap(),
+        TimeControl::with_periods(vec![
+            Period { moves: Some(40), ..Period::new(secs(5400)) },
+            Period::new(secs(1800)),
+        ])
+        .unwrap()
+    );
+    assert_eq!(TimeControl::with_periods(Vec::new()), Err(TimeControlError::NoPeriods));
+    assert_eq!("60+2".parse::<TimeControl>().unwrap(), TimeControl::fischer(secs(60), secs(2)));
+    assert_eq!("60b3".parse::<TimeControl>().unwrap(), TimeControl::bronstein(secs(60), secs(3)));
+    assert_eq!("60d3".parse::<TimeControl>().unwrap(), TimeControl::simple_delay(secs(60), secs(3)));
+
+    for bad in ["", "abc", "0", "0/60", "40/", "-5", "60+x", "60d", "inf"] {
+        assert_eq!(bad.parse::<TimeControl>(), Err(TimeControlError::Unreadable(bad.to_string())), "{}", bad);
+    }
+}
+
+#[test]
+fn flag_against_a_side_that_cannot_mate_is_a_draw() {
+    let (mut clock, time) = clock("10");
+    clock.start(Color::White);
+    time.advance(secs(11));
+
+    // black has only a knight against a bare king, so white running out of time draws
+    let knight = Board::from_fen("4k3/8/8/8/8/8/8/4K1n1 w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&knight, &clock), GameResult::Draw);
+    let bare_king = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&bare_king, &clock), GameResult::Draw);
+    // but with a pawn or a rook on white's side there are mates to be had
+    let knight_against_pawn = Board::from_fen("4k3/8/8/8/8/8/4P3/4K1n1 w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&knight_against_pawn, &clock), GameResult::BlackWins);
+    let bishop_against_rook = Board::from_fen("4k3/8/8/8/8/8/8/R3K1b1 w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&bishop_against_rook, &clock), GameResult::BlackWins);
+    let two_knights = Board::from_fen("4k3/8/8/8/8/8/4P3/4Kn1n w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&two_knights, &clock), GameResult::BlackWins);
+    let pawn = Board::from_fen("4k3/p7/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&pawn, &clock), GameResult::BlackWins);
+
+    // a mate on the board counts before the flag
+    let mated = Board::from_fen("4k3/8/8/8/8/8/5PPP/3r2K1 w - - 0 1").unwrap();
+    assert_eq!(result_with_clock(&mated, &clock), GameResult::BlackWins);
+    let (fresh, _) = self::clock("10");
+    assert_eq!(result_with_clock(&pawn, &fresh), GameResult::Ongoing);
+}
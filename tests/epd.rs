@@ -0,0 +1,67 @@
+use rust_chess::*;
+
+#[test]
+fn reads_operations_after_the_position() {
+    let epd: Epd = r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001"; c0 "a quiet; but deadly move";"#
+        .parse()
+        .unwrap();
+    assert_eq!(epd.board.to_fen(), "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1");
+    assert_eq!(epd.id(), Some("WAC.001"));
+    assert_eq!(epd.operation("c0").unwrap(), ["a quiet; but deadly move"]);
+    assert_eq!(epd.best_moves().unwrap(), [epd.board.parse_san("Qg6").unwrap()]);
+    assert!(epd.avoid_moves().unwrap().is_empty());
+
+    // and writes them back the same way
+    assert_eq!(epd.to_string().parse::<Epd>().unwrap(), epd);
+    assert!(epd.to_string().ends_with(r#"bm Qg6; id "WAC.001"; c0 "a quiet; but deadly move";"#));
+}
+
+#[test]
+fn takes_move_numbers_from_a_full_fen_or_opcodes() {
+    let full: Epd = "8/8/8/4k3/8/8/8/4K3 w - - 12 40 bm Kd2".parse().unwrap();
+    assert_eq!(full.board.to_fen(), "8/8/8/4k3/8/8/8/4K3 w - - 12 40");
+    assert_eq!(full.operations, [("bm".to_string(), vec!["Kd2".to_string()])]);
+
+    let opcodes: Epd = "8/8/8/4k3/8/8/8/4K3 w - - hmvc 7; fmvn 31;".parse().unwrap();
+    assert_eq!(opcodes.board.to_fen(), "8/8/8/4k3/8/8/8/4K3 w - - 7 31");
+}
+
+#[test]
+fn rejects_what_it_cannot_read() {
+    let bad = |text: &str| text.parse::<Epd>().unwrap_err();
+    assert!(matches!(bad("8/8/8/4k3/8/8/8/4K3 w -"), EpdError::BadFen(_)));
+    assert!(matches!(bad("8/8/8/4k3/8/8/8/4K3 w - - 3bm Kd2;"), EpdError::BadOperation(_)));
+    assert!(matches!(bad(r#"8/8/8/4k3/8/8/8/4K3 w - - id "open;"#), EpdError::BadOperation(_)));
+
+    let epd: Epd = "8/8/8/4k3/8/8/8/4K3 w - - bm Ke5;".parse().unwrap();
+    assert!(matches!(epd.best_moves(), Err(EpdError::BadMove { .. })));
+}
+
+#[test]
+fn counts_the_positions_a_suite_solves() {
+    let suite = "
+        # mates in one, and a move to stay away from
+        6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"back rank\";
+        k7/8/1K6/8/8/8/7Q/8 w - - bm Qb8+ Qa2+; id \"wrong\";
+        4k3/8/8/8/8/8/8/R3K3 w - - am Kd2 Kf2; id \"avoid\";
+    ";
+    let positions: Vec<Epd> = read_epd(suite).into_iter().map(Result::unwrap).collect();
+    assert_eq!(positions.len(), 3);
+
+    let mut seen = Vec::new();
+    let score = run_suite(&mut Searcher::new(), &positions, SearchLimits::depth(2), |position, outcome, _| {
+        seen.push((position.id().unwrap().to_string(), outcome.solved));
+    })
+    .unwrap();
+    // the mate is 1.Qh8#, the checks it was given are not
+    assert_eq!(score, SuiteScore { solved: 2, failed: 1 });
+    assert_eq!(score.total(), 3);
+    assert_eq!(
+        seen,
+        [("back rank".to_string(), true), ("wrong".to_string(), false), ("avoid".to_string(), true)]
+    );
+
+    let untested = vec!["6k1/5ppp/8/8/8/8/8/R5K1 w - - id \"nothing to find\";".parse().unwrap()];
+    let error = run_suite(&mut Searcher::new(), &untested, SearchLimits::depth(1), |_, _, _| ()).unwrap_err();
+    assert_eq!(error, EpdError::NoSolution("nothing to find".to_string()));
+}
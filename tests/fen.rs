@@ -0,0 +1,52 @@
+use rust_chess::*;
+
+#[test]
+fn fen_round_trips() {
+    let fens = [
+        STARTING_FEN,
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 b Q - 37 52",
+        "8/8/8/4k3/8/8/8/4K3 w - - 99 120",
+        "8/8/8/4k3/8/8/8/4K3 b - - 0 200",
+        "8/8/8/4k3/8/8/8/4K3 w - - 12 4000",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(Board::from_fen(&board.to_fen()).unwrap(), board);
+    }
+}
+
+#[test]
+fn missing_move_numbers_default() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w -  -").unwrap();
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+}
+
+#[test]
+fn shredder_fen_names_the_rook_files() {
+    let board = Board::from_fen("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1").unwrap();
+    assert_eq!(board.to_shredder_fen(), "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1");
+    assert_eq!(board.to_fen(), "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w KQkq - 0 1");
+}
+
+#[test]
+fn rejects_malformed_fen() {
+    let bad = |fen: &str| Board::from_fen(fen).unwrap_err();
+
+    assert_eq!(bad(""), FenError::WrongFieldCount(0));
+    assert_eq!(bad("8/8/8/8/8/8/8/8 w"), FenError::WrongFieldCount(2));
+    assert_eq!(bad("4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra"), FenError::WrongFieldCount(7));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8 w - - 0 1"), FenError::BadPlacement(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4K4 w - - 0 1"), FenError::BadPlacement(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4X3 w - - 0 1"), FenError::BadPlacement(_)));
+    assert_eq!(bad("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), FenError::BadSideToMove("x".to_string()));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4K3 w KZ - 0 1"), FenError::BadCastling(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4K3 w - e9 0 1"), FenError::BadEnPessant(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4K3 w - e3 0 1"), FenError::BadEnPessant(_)));
+    assert_eq!(bad("4k3/8/8/8/8/8/8/4K3 w - - x 1"), FenError::BadMoveNumber("x".to_string()));
+    assert_eq!(bad("4k3/8/8/8/8/8/8/4K3 w - - 0 -3"), FenError::BadMoveNumber("-3".to_string()));
+    assert!(matches!(bad("8/8/8/8/8/8/8/4K3 w - - 0 1"), FenError::IllegalPosition(_)));
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_chess::*;
+
+fn game_from(fen: &str, moves: &[&str]) -> Game {
+    let mut game = Game::from_position(Board::from_fen(fen).unwrap());
+    for text in moves {
+        game.play_str(text).unwrap();
+    }
+    game
+}
+
+fn timed_game(fen: &str, control: &str) -> (Game, ManualTime) {
+    let time = ManualTime::new();
+    let mut clock = Clock::with_time_source(control.parse().unwrap(), Arc::new(time.clone()));
+    clock.start(Color::White);
+    let mut game = Game::from_position(Board::from_fen(fen).unwrap());
+    game.clock = Some(clock);
+    (game, time)
+}
+
+#[test]
+fn undo_takes_back_the_move_and_its_result() {
+    let mut game = game_from(STARTING_FEN, &["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6"]);
+    let before = game.board().clone();
+    game.play_str("Qxf7#").unwrap();
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert_eq!(game.termination(), Some(Termination::Checkmate));
+
+    let record = game.undo().unwrap();
+    assert_eq!(record.san, "Qxf7#");
+    assert_eq!(game.board(), &before);
+    assert_eq!(game.board().to_fen(), before.to_fen());
+    assert_eq!(game.result(), GameResult::Ongoing);
+    assert_eq!(game.termination(), None);
+    assert_eq!(game.moves().len(), 6);
+    assert_eq!(game.move_number(), 4);
+
+    game.resign(Color::White);
+    assert_eq!(game.result(), GameResult::BlackWins);
+    game.undo();
+    assert!(!game.is_over());
+
+    let mut fresh = Game::new();
+    assert!(fresh.undo().is_none());
+}
+
+#[test]
+fn threefold_repetition() {
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    let mut game = game_from(STARTING_FEN, &shuffle);
+    assert!(!game.is_threefold_repetition());
+    assert!(!game.claim_draw());
+
+    for text in shuffle {
+        game.play_str(text).unwrap();
+    }
+    assert!(game.is_threefold_repetition());
+    game.undo();
+    assert!(!game.is_threefold_repetition());
+    game.play_str("Ng8").unwrap();
+
+    assert!(game.claim_draw());
+    assert_eq!(game.result(), GameResult::Draw);
+    assert_eq!(game.termination(), Some(Termination::Repetition));
+}
+
+#[test]
+fn repetition_ignores_an_en_passant_square_nobody_can_use() {
+    // after e4 the e3 square is set but black has no pawn to take with, so
+    // the position is the same as when the king comes back to e1
+    let mut game = game_from("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", &["e4", "Kd8", "Ke2", "Ke8", "Ke1"]);
+    assert!(!game.is_threefold_repetition());
+    for text in ["Kd8", "Ke2", "Ke8", "Ke1"] {
+        game.play_str(text).unwrap();
+    }
+    assert!(game.is_threefold_repetition());
+}
+
+#[test]
+fn repetition_counts_an_en_passant_capture_as_different() {
+    // right after d5 white could take en passant, later it cannot
+    let mut game = game_from("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1", &["d5"]);
+    for text in ["Kd1", "Kd8", "Ke1", "Ke8", "Kd1", "Kd8", "Ke1", "Ke8"] {
+        game.play_str(text).unwrap();
+    }
+    assert!(!game.is_threefold_repetition());
+    for text in ["Kd1", "Kd8", "Ke1", "Ke8"] {
+        game.play_str(text).unwrap();
+    }
+    assert!(game.is_threefold_repetition());
+}
+
+#[test]
+fn fifty_move_rule() {
+    let mut game = game_from("4k3/8/8/8/8/8/8/R3K3 w - - 98 80", &["Ra2"]);
+    assert!(!game.is_fifty_move_draw());
+    assert!(!game.claim_draw());
+    game.play_str("Kd7").unwrap();
+    assert!(game.is_fifty_move_draw());
+    // it has to be claimed
+    assert!(!game.is_over());
+    assert!(game.claim_draw());
+    assert_eq!(game.termination(), Some(Termination::FiftyMoves));
+    assert_eq!(game.result(), GameResult::Draw);
+
+    // a pawn move starts the count again
+    let game = game_from("4k3/8/8/8/8/8/4P3/R3K3 w - - 98 80", &["e4", "Kd7"]);
+    assert!(!game.is_fifty_move_draw());
+}
+
+#[test]
+fn flag_fall_ends_the_game() {
+    let (mut game, time) = timed_game(STARTING_FEN, "10");
+    time.advance(Duration::from_secs(3));
+    game.play_str("e4").unwrap();
+    assert!(!game.check_flag());
+
+    time.advance(Duration::from_secs(11));
+    assert!(game.check_flag());
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert_eq!(game.termination(), Some(Termination::TimeForfeit));
+    assert_eq!(game.play_str("e5"), Err(MoveError::GameOver("e5".to_string())));
+    assert_eq!(game.moves().len(), 1);
+}
+
+#[test]
+fn flag_fall_against_a_bare_king_is_a_draw() {
+    let (mut game, time) = timed_game("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", "10");
+    time.advance(Duration::from_secs(11));
+    // the move comes too late
+    assert!(game.play_str("e4").is_err());
+    assert_eq!(game.result(), GameResult::Draw);
+    assert_eq!(game.termination(), Some(Termination::TimeForfeit));
+}
+
+#[test]
+fn termination_tags() {
+    let mut game = game_from(STARTING_FEN, &["e4", "e5"]);
+    game.resign(Color::Black);
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[Result \"1-0\"]"));
+    assert!(pgn.contains("[Termination \"normal\"]"));
+
+    let (mut game, time) = timed_game(STARTING_FEN, "10");
+    time.advance(Duration::from_secs(11));
+    game.check_flag();
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[Result \"0-1\"]"));
+    assert!(pgn.contains("[Termination \"time forfeit\"]"));
+    let read = Game::from_pgn(&pgn).unwrap();
+    assert_eq!(read.termination(), Some(Termination::TimeForfeit));
+    assert_eq!(read.result(), GameResult::BlackWins);
+
+    let mate = game_from(STARTING_FEN, &["f3", "e5", "g4", "Qh4#"]);
+    let read = Game::from_pgn(&mate.to_pgn()).unwrap();
+    assert_eq!(read.termination(), Some(Termination::Checkmate));
+    assert_eq!(read.tag("Termination"), Some("normal"));
+
+    let mut adjudicated = Game::new();
+    adjudicated.finish(GameResult::Draw, Termination::Adjudication);
+    assert!(adjudicated.to_pgn().contains("[Termination \"adjudication\"]"));
+    // finishing again changes nothing
+    adjudicated.resign(Color::White);
+    assert_eq!(adjudicated.termination(), Some(Termination::Adjudication));
+}
@@ -0,0 +1,113 @@
+use rust_chess::*;
+
+fn player(name: &str, depth: u32) -> SearcherPlayer {
+    let mut player = SearcherPlayer::new(name, Searcher::new());
+    player.depth = Some(depth);
+    player
+}
+
+#[test]
+fn elo_and_error_bars() {
+    let even = MatchScore { wins: 10, draws: 20, losses: 10 };
+    assert_eq!(even.elo(), 0.0);
+    let ahead = MatchScore { wins: 30, draws: 20, losses: 10 };
+    // two thirds of the points is about 120 Elo
+    assert!((ahead.elo() - 120.4).abs() < 0.1, "{}", ahead.elo());
+    assert!(ahead.elo_error() > 0.0 && ahead.elo_error() < 100.0);
+    // more games, smaller error
+    let more = MatchScore { wins: 300, draws: 200, losses: 100 };
+    assert!(more.elo_error() < ahead.elo_error() / 3.0);
+    assert_eq!(ahead.to_string(), "+30 =20 -10");
+}
+
+#[test]
+fn sprt_passes_and_fails() {
+    let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+    let (lower, upper) = sprt.bounds();
+    assert!((upper - 2.944).abs() < 0.001 && (lower + 2.944).abs() < 0.001);
+    assert_eq!(sprt.status(&MatchScore::default()), SprtStatus::Continue);
+    assert_eq!(sprt.status(&MatchScore { wins: 10, draws: 10, losses: 8 }), SprtStatus::Continue);
+    assert_eq!(sprt.status(&MatchScore { wins: 700, draws: 1000, losses: 500 }), SprtStatus::Pass);
+    assert_eq!(sprt.status(&MatchScore { wins: 500, draws: 1000, losses: 600 }), SprtStatus::Fail);
+    // only wins still decide it
+    assert_eq!(sprt.status(&MatchScore { wins: 200, draws: 0, losses: 0 }), SprtStatus::Pass);
+}
+
+#[test]
+fn plays_each_opening_with_both_colors() {
+    let opening = Opening::new(Board::from_fen("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1").unwrap());
+    let settings = MatchSettings {
+        games: 2,
+        adjudication: Adjudication { max_moves: Some(5), ..Default::default() },
+        ..Default::default()
+    };
+    let (mut first, mut second) = (player("first", 1), player("second", 1));
+    let mut games = Vec::new();
+    let score = play_match(&mut first, &mut second, std::slice::from_ref(&opening), &settings, |game, _| {
+        games.push(game.clone())
+    })
+    .unwrap();
+
+    assert_eq!(score.games(), 2);
+    assert_eq!((games[0].white.as_str(), games[0].black.as_str()), ("first", "second"));
+    assert_eq!((games[1].white.as_str(), games[1].black.as_str()), ("second", "first"));
+    for game in &games {
+        assert_eq!(game.start(), &opening.start);
+        assert_eq!(game.moves().len(), 10);
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::Adjudication));
+        assert!(game.to_pgn().contains("[Termination \"adjudication\"]"));
+    }
+}
+
+// gives up every game before making a move
+struct Resigner;
+
+impl Player for Resigner {
+    fn name(&self) -> &str {
+        "resigner"
+    }
+
+    fn new_game(&mut self) {}
+
+    fn play(&mut self, _game: &Game) -> Result<PlayerMove, String> {
+        Err("resigns".to_string())
+    }
+}
+
+#[test]
+fn sprt_waits_for_both_colors() {
+    // any lead at all decides a test this loose, still the opening is
+    // played with the other colors before it stops
+    let settings = MatchSettings {
+        games: 10,
+        sprt: Some(Sprt::new(0.0, 10.0, 0.5, 0.5)),
+        ..Default::default()
+    };
+    let mut rounds = Vec::new();
+    let score = play_match(&mut Resigner, &mut player("winner", 1), &[], &settings, |game, _| {
+        rounds.push(game.round.clone())
+    })
+    .unwrap();
+    assert_eq!(rounds, ["1", "2"]);
+    assert_eq!(score, MatchScore { wins: 0, draws: 0, losses: 2 });
+}
+
+#[test]
+fn adjudicates_a_lost_game() {
+    // a queen up, both sides soon agree
+    let opening = Opening::new(Board::from_fen("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1").unwrap());
+    let resign = Adjudication {
+        resign: Some(ResignRule { moves: 2, score: 600 }),
+        ..Default::default()
+    };
+    let game = play_game(&mut player("white", 2), &mut player("black", 2), &opening, None, &resign).unwrap();
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert_eq!(game.moves().len(), 4);
+
+    let time_control: TimeControl = "5+0.1".parse().unwrap();
+    let game = play_game(&mut player("white", 1), &mut player("black", 1), &opening, Some(&time_control), &resign)
+        .unwrap();
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert!(game.moves().iter().all(|record| record.clock.is_some()));
+}
@@ -0,0 +1,30 @@
+use rust_chess::*;
+
+// Node counts from the chess programming wiki's perft results page.
+
+#[test]
+fn start_position() {
+    let board = Board::default();
+    assert_eq!(perft(&board, 1), 20);
+    assert_eq!(perft(&board, 2), 400);
+    assert_eq!(perft(&board, 3), 8902);
+    assert_eq!(perft(&board, 4), 197281);
+}
+
+#[test]
+fn kiwipete() {
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(perft(&board, 1), 48);
+    assert_eq!(perft(&board, 2), 2039);
+    assert_eq!(perft(&board, 3), 97862);
+}
+
+#[test]
+fn en_passant_and_promotion_positions() {
+    // position 3, where en passant can uncover a check along the rank
+    let board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+    assert_eq!(perft(&board, 4), 43238);
+    // position 4, full of promotions and castling into check
+    let board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+    assert_eq!(perft(&board, 3), 9467);
+}
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use rust_chess::*;
+
+const ANNOTATED: &str = r#"[Event "Casual game"]
+[Site "London"]
+[Date "1851.06.21"]
+[Round "?"]
+[White "Anderssen, Adolf"]
+[Black "Kieseritzky, Lionel"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 $1 exf4 {the gambit is accepted} 3. Bc4 Qh4+ 4. Kf1 b5?! (4... Nf6
+5. Nc3) 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5 8. Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1
+cxb5 12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18.
+Bd6 Bxg1 (18... Qxa1+ 19. Ke2 Qb2 (19... Qxg1)) 19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+
+Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+
+#[test]
+fn reads_past_variations_and_nags() {
+    let game = Game::from_pgn(ANNOTATED).unwrap();
+    assert_eq!(game.white, "Anderssen, Adolf");
+    assert_eq!(game.moves().len(), 45);
+    assert_eq!(game.moves()[3].san, "exf4");
+    assert_eq!(game.moves()[3].comment.as_deref(), Some("the gambit is accepted"));
+    assert_eq!(game.moves()[7].san, "b5");
+    assert_eq!(game.moves()[8].san, "Bxb5");
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert!(game.board().is_checkmate());
+}
+
+#[test]
+fn write_then_read_keeps_the_game() {
+    let game = Game::from_pgn(ANNOTATED).unwrap();
+    let written = game.to_pgn();
+    // the variations and glyphs are not part of the game, the comment is
+    assert!(!written.contains('('));
+    assert!(!written.contains('$'));
+    assert!(written.contains("{the gambit is accepted}"));
+
+    let read = Game::from_pgn(&written).unwrap();
+    assert_eq!(read.moves(), game.moves());
+    assert_eq!(read.board(), game.board());
+    assert_eq!(read.result(), game.result());
+    assert_eq!(read.tag("Date"), Some("1851.06.21"));
+    assert_eq!(read.to_pgn(), written);
+}
+
+#[test]
+fn round_trips_comments_and_a_set_up_position() {
+    let start = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30").unwrap();
+    let mut game = Game::from_position(start);
+    game.set_tag("Annotator", "someone \"quoted\"");
+    game.play_str("Kd7").unwrap();
+    game.comment("a comment with } in it");
+    game.play_str("e4").unwrap();
+    game.play_str("Ke6").unwrap();
+    game.comment("another");
+
+    let written = game.to_pgn();
+    assert!(written.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]"));
+    assert!(written.contains("30... Kd7 {a comment with ) in it} 31. e4 Ke6 {another} *"));
+
+    let read = Game::from_pgn(&written).unwrap();
+    assert_eq!(read.start(), game.start());
+    assert_eq!(read.tag("Annotator"), Some("someone \"quoted\""));
+    let comments: Vec<Option<&str>> = read.moves().iter().map(|record| record.comment.as_deref()).collect();
+    assert_eq!(comments, [Some("a comment with ) in it"), None, Some("another")]);
+    assert_eq!(read.board(), game.board());
+}
+
+#[test]
+fn unmatched_parentheses_are_errors() {
+    assert_eq!(Game::from_pgn("1. e4 e5) 2. Nf3 Nc6 *").unwrap_err(), PgnError::UnmatchedParenthesis(')'));
+    assert_eq!(Game::from_pgn("1. e4 e5 (2. d4 2. Nf3 Nc6 *").unwrap_err(), PgnError::UnmatchedParenthesis('('));
+
+    // only the game with the stray parenthesis is lost
+    let games = read_pgn_games("[Event \"a\"]\n\n1. e4 e5) *\n\n[Event \"b\"]\n\n1. d4 (1. c4) d5 *\n");
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].as_ref().unwrap_err(), &PgnError::UnmatchedParenthesis(')'));
+    assert_eq!(games[1].as_ref().unwrap().moves().len(), 2);
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let time = ManualTime::new();
+    let mut clock = Clock::with_time_source("40/5400:1800+30".parse().unwrap(), Arc::new(time.clone()));
+    clock.start(Color::White);
+    let mut game = Game::new();
+    game.white = "White player".to_string();
+    game.clock = Some(clock);
+    for (text, thinking) in [("e4", 12.3), ("c5", 4.0), ("Nf3", 61.0), ("d6", 0.5)] {
+        time.advance(Duration::from_secs_f64(thinking));
+        game.play_str(text).unwrap();
+    }
+    game.comment("the Najdorf is coming");
+
+    let path = std::env::temp_dir().join(format!("rust_chess_save_test_{}.pgn", std::process::id()));
+    game.save(&path).unwrap();
+    let loaded = Game::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let mut loaded = loaded.unwrap();
+
+    assert_eq!(loaded.white, "White player");
+    type Saved = (Move, String, Option<SystemTime>, Option<Duration>, Option<String>);
+    let saved = |game: &Game| -> Vec<Saved> {
+        game.moves()
+            .iter()
+            .map(|record| (record.mv, record.san.clone(), record.played_at, record.clock, record.comment.clone()))
+            .collect()
+    };
+    assert_eq!(saved(&loaded), saved(&game));
+    assert!(loaded.moves().iter().all(|record| record.played_at.is_some()));
+    assert_eq!(loaded.board(), game.board());
+    assert_eq!(loaded.moves()[0].clock, Some(Duration::from_millis(5_387_700)));
+
+    // the clock comes back stopped with what each side had left, ready to
+    // start for the side to move the way resuming a game does
+    let side_to_move = loaded.side_to_move();
+    let clock = loaded.clock.as_mut().unwrap();
+    assert_eq!(clock.control().to_string(), "40/5400:1800+30");
+    assert_eq!(clock.running(), None);
+    assert_eq!(clock.remaining(Color::White), Duration::from_millis(5_400_000 - 12_300 - 61_000));
+    assert_eq!(clock.remaining(Color::Black), Duration::from_millis(5_400_000 - 4_000 - 500));
+    assert_eq!(clock.moves_to_go(Color::White), Some(38));
+    clock.start(side_to_move);
+    assert_eq!(clock.running(), Some(Color::White));
+
+    assert!(Game::load(std::env::temp_dir().join("rust_chess_no_such_game.pgn")).is_err());
+}
@@ -0,0 +1,173 @@
+use rust_chess::*;
+use std::fs;
+
+const BOOK: &str = "tests/data/small_book.bin";
+const GAMES: &str = "tests/data/openings.pgn";
+
+// reference keys from the Polyglot format description
+#[test]
+fn keys_match_reference_values() {
+    let reference = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+        ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+        ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+        ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+        ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+        ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560),
+    ];
+    for (fen, key) in reference {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.polyglot_key(), key, "{}", fen);
+    }
+}
+
+// The same reference positions reached by playing the moves, so the keys
+// follow castling rights and en passant squares as the game changes them.
+#[test]
+fn keys_follow_the_moves() {
+    let lines: [&[(&str, u64)]; 2] = [
+        &[
+            ("e4", 0x823c9b50fd114196),
+            ("d5", 0x0756b94461c50fb0),
+            ("e5", 0x662fafb965db29d4),
+            ("f5", 0x22a48b5a8e47ff78),
+            ("Ke2", 0x652a607ca3f242c1),
+            ("Kf7", 0x00fdd303c946bdd9),
+        ],
+        // only the keys after c4 and Ra3 are published for this line
+        &[
+            ("a4", 0),
+            ("b5", 0),
+            ("h4", 0),
+            ("b4", 0),
+            ("c4", 0x3c8123ea7b067637),
+            ("bxc3", 0),
+            ("Ra3", 0x5c3f9b829b279560),
+        ],
+    ];
+    for line in lines {
+        let mut board = Board::default();
+        for &(san, key) in line {
+            let mv = board.parse_san(san).unwrap();
+            assert!(board.play_move(&mv));
+            if key != 0 {
+                assert_eq!(board.polyglot_key(), key, "after {}", san);
+            }
+        }
+    }
+}
+
+// A book written out byte by byte from the Polyglot format description, so
+// reading it does not lean on BookBuilder or encode_book_move: each entry is
+// the key, the move as to file, to row, from file, from row and promotion in
+// 3 bits each from the lowest, the weight and the learn field, all big endian.
+fn entry(key: u64, mv: u16, weight: u16) -> Vec<u8> {
+    let mut bytes = key.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&mv.to_be_bytes());
+    bytes.extend_from_slice(&weight.to_be_bytes());
+    bytes.extend_from_slice(&0u32.to_be_bytes());
+    bytes
+}
+
+const E2E4: u16 = 4 | 3 << 3 | 4 << 6 | 1 << 9;
+const D2D4: u16 = 3 | 3 << 3 | 3 << 6 | 1 << 9;
+const G1F3: u16 = 5 | 2 << 3 | 6 << 6;
+const E4D5: u16 = 3 | 4 << 3 | 4 << 6 | 3 << 9;
+const E1H1: u16 = 7 | 4 << 6; // castling is written as the king taking its rook
+const E1A1: u16 = 4 << 6;
+const E8H8: u16 = 7 | 7 << 3 | 4 << 6 | 7 << 9;
+const E7E8N: u16 = 4 | 7 << 3 | 4 << 6 | 6 << 9 | 1 << 12;
+const E7E8Q: u16 = 4 | 7 << 3 | 4 << 6 | 6 << 9 | 4 << 12;
+
+#[test]
+fn reads_a_book_written_to_the_format() {
+    let castling = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let black_castling = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+    let promotion = Board::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let mut entries = vec![
+        entry(0x463b96181691fc9c, E2E4, 40),
+        entry(0x463b96181691fc9c, D2D4, 25),
+        entry(0x463b96181691fc9c, G1F3, 7),
+        entry(0x0756b94461c50fb0, E4D5, 3),
+        entry(castling.polyglot_key(), E1H1, 2),
+        entry(castling.polyglot_key(), E1A1, 1),
+        entry(black_castling.polyglot_key(), E8H8, 1),
+        entry(promotion.polyglot_key(), E7E8Q, 9),
+        entry(promotion.polyglot_key(), E7E8N, 1),
+    ];
+    // books are sorted by key, the order within a key does not matter
+    entries.sort();
+    let bytes = entries.concat();
+    let book = OpeningBook::from_bytes(&bytes).unwrap();
+    assert_eq!(book.len(), 9);
+
+    let san = |board: &Board| -> Vec<(String, u16)> {
+        book.moves(board).into_iter().map(|(mv, weight)| (board.move_to_san(&mv), weight)).collect()
+    };
+    let start = Board::default();
+    assert_eq!(san(&start), [("e4".to_string(), 40), ("d4".to_string(), 25), ("Nf3".to_string(), 7)]);
+
+    let mut scandinavian = start.clone();
+    for text in ["e4", "d5"] {
+        scandinavian.play_move(&scandinavian.parse_san(text).unwrap());
+    }
+    assert_eq!(san(&scandinavian), [("exd5".to_string(), 3)]);
+    assert_eq!(san(&castling), [("O-O".to_string(), 2), ("O-O-O".to_string(), 1)]);
+    assert_eq!(san(&black_castling), [("O-O".to_string(), 1)]);
+    assert_eq!(san(&promotion), [("e8=Q".to_string(), 9), ("e8=N+".to_string(), 1)]);
+    assert_eq!(book.best_move(&promotion), promotion.parse_san("e8=Q").ok());
+
+    // and it is written back the same
+    assert_eq!(book.to_bytes(), bytes);
+}
+
+#[test]
+fn moves_survive_encoding() {
+    let board = Board::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    for mv in board.legal_moves() {
+        assert_eq!(decode_book_move(encode_book_move(&mv)), mv);
+    }
+}
+
+#[test]
+fn bundled_book_has_the_expected_moves() {
+    let book = OpeningBook::open(BOOK).unwrap();
+    let start = Board::default();
+
+    // 1. e4 scored 2 + 1 + 2 + 0, 1. d4 scored 1 + 2 and 1. c4 nothing
+    let moves: Vec<(String, u16)> = book
+        .moves(&start)
+        .into_iter()
+        .map(|(mv, weight)| (start.move_to_san(&mv), weight))
+        .collect();
+    assert_eq!(moves, [("e4".to_string(), 5), ("d4".to_string(), 3), ("c4".to_string(), 0)]);
+    assert_eq!(book.choose_move(&start, BookSelection::Best), start.parse_san("e4").ok());
+
+    // the roll picks e4 for the first five points and d4 for the next three
+    let picks: Vec<Option<Move>> = (0..8).map(|roll| book.weighted_move(&start, roll)).collect();
+    assert!(picks[..5].iter().all(|&mv| mv == start.parse_san("e4").ok()));
+    assert!(picks[5..].iter().all(|&mv| mv == start.parse_san("d4").ok()));
+
+    // castling comes back as the king taking its rook, which is how Move has it
+    let game = read_pgn("1. e4 e5 2. Nf3 Nc6 3. Bb5 Nf6 *").unwrap();
+    let castling = book.best_move(game.board()).unwrap();
+    assert_eq!(game.board().move_to_san(&castling), "O-O");
+
+    let unknown = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(book.moves(&unknown).is_empty());
+}
+
+#[test]
+fn building_from_the_games_gives_the_bundled_book() {
+    let mut builder = BookBuilder::new();
+    builder.max_ply = 10;
+    for game in read_pgn_games(&fs::read_to_string(GAMES).unwrap()) {
+        builder.add_game(&game.unwrap());
+    }
+    let built = builder.build();
+    assert_eq!(built.to_bytes(), fs::read(BOOK).unwrap());
+    assert_eq!(OpeningBook::from_bytes(&built.to_bytes()).unwrap(), built);
+}
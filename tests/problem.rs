@@ -0,0 +1,54 @@
+use rust_chess::*;
+
+fn san(board: &Board, moves: &[Move]) -> Vec<String> {
+    let mut moves: Vec<String> = moves.iter().map(|mv| board.move_to_san(mv)).collect();
+    moves.sort();
+    moves
+}
+
+#[test]
+fn proves_and_disproves_direct_mates() {
+    // Morphy: 1.Ra6! bxa6 2.b7#, and the bishop's moves allow 2.Rxa7#
+    let morphy = Board::from_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1").unwrap();
+    assert!(morphy.solve(Stipulation::Mate, 1).is_none());
+    let solution = morphy.solve(Stipulation::Mate, 2).unwrap();
+    assert_eq!(san(&morphy, &solution.key_moves()), ["Ra6"]);
+
+    // every defence is in the tree, each answered with a mate
+    let key = &solution.tree[0];
+    let mut after_key = morphy.clone();
+    assert!(after_key.play_move(&key.mv));
+    assert_eq!(key.continuations.len(), after_key.legal_moves().len());
+    for defence in &key.continuations {
+        let mut board = after_key.clone();
+        assert!(board.play_move(&defence.mv));
+        assert!(!defence.continuations.is_empty());
+        for mate in &defence.continuations {
+            let mut mated = board.clone();
+            assert!(mated.play_move(&mate.mv));
+            assert!(mated.is_checkmate());
+        }
+    }
+    assert!(solution.render(&morphy).starts_with("1.Ra6!\n  1...Bc7\n    2.Rxa7#\n"));
+}
+
+#[test]
+fn finds_every_key_move() {
+    let back_rank = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R2R2K1 w - - 0 1").unwrap();
+    let solution = back_rank.solve(Stipulation::Mate, 1).unwrap();
+    assert_eq!(san(&back_rank, &solution.key_moves()), ["Ra8#", "Rd8#"]);
+}
+
+#[test]
+fn solves_helpmates_and_selfmates() {
+    // white helps black to the fool's mate
+    let start = Board::default();
+    assert!(start.solve(Stipulation::Helpmate, 1).is_none());
+    let solution = start.solve(Stipulation::Helpmate, 2).unwrap();
+    assert_eq!(san(&start, &solution.key_moves()), ["f3", "f4", "g4"]);
+
+    // 1.Qb2+! leaves black nothing but 1...Qxb2#
+    let board = Board::from_fen("1q6/8/n7/5p2/5r2/K7/7Q/k7 w - - 0 1").unwrap();
+    let solution = board.solve(Stipulation::Selfmate, 1).unwrap();
+    assert_eq!(solution.render(&board), "1.Qb2+!\n  1...Qxb2#\n");
+}
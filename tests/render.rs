@@ -0,0 +1,89 @@
+use rust_chess::*;
+
+// the scholar's mate, black to move and mated
+const MATE: &str = "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4";
+
+fn sorted(mut squares: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    squares.sort();
+    squares
+}
+
+#[test]
+fn attack_queries() {
+    let board = Board::from_fen(MATE).unwrap();
+    assert_eq!(board.king_position(Color::White), Some((7, 4)));
+    assert_eq!(board.king_position(Color::Black), Some((0, 4)));
+    assert!(board.is_in_check(Color::Black));
+    assert!(!board.is_in_check(Color::White));
+
+    // the queen on f7 gives check, guarded by the bishop on c4
+    assert_eq!(board.attackers((0, 4), Color::White), vec![(1, 5)]);
+    assert_eq!(board.attackers((1, 5), Color::White), vec![(4, 2)]);
+    assert_eq!(board.attackers((1, 5), Color::Black), vec![(0, 4)]);
+    // d5 is hit by the queen, the bishop and the e4 pawn, and by the f6
+    // knight but not the queen on d8 behind its own pawn
+    assert_eq!(sorted(board.attackers((3, 3), Color::White)), vec![(1, 5), (4, 2), (4, 4)]);
+    assert_eq!(board.attackers((3, 3), Color::Black), vec![(2, 5)]);
+    assert!(board.is_square_attacked((3, 3), Color::Black));
+    assert!(board.is_square_attacked((5, 0), Color::Black)); // the f8 bishop
+    assert!(!board.is_square_attacked((5, 7), Color::Black));
+    // pawns only attack forwards
+    assert_eq!(sorted(board.attackers((5, 4), Color::White)), vec![(6, 3), (6, 5)]);
+    assert!(board.attackers((7, 3), Color::White).contains(&(7, 4)));
+    assert!(!board.attackers((7, 3), Color::White).contains(&(6, 2)));
+
+    let empty = Board { board: [[None; 8]; 8], ..Default::default() };
+    assert_eq!(empty.king_position(Color::White), None);
+    assert!(!empty.is_in_check(Color::White));
+}
+
+#[test]
+fn renders_coordinates_and_highlights() {
+    let board = Board::from_fen(MATE).unwrap();
+    let options = RenderOptions {
+        coordinates_both_sides: true,
+        last_move: Some(((3, 7), (1, 5))),
+        highlight_check: true,
+        ..Default::default()
+    };
+    let expected = "    A   B   C   D   E   F   G   H 
+  ---------------------------------
+8 | r |   | b | q |(k)| b |   | r | 8
+  ---------------------------------
+7 | p | p | p | p |   |[Q]| p | p | 7
+  ---------------------------------
+6 |   |   | n |   |   | n |   |   | 6
+  ---------------------------------
+5 |   |   |   |   | p |   |   |[ ]| 5
+  ---------------------------------
+4 |   |   | B |   | P |   |   |   | 4
+  ---------------------------------
+3 |   |   |   |   |   |   |   |   | 3
+  ---------------------------------
+2 | P | P | P | P |   | P | P | P | 2
+  ---------------------------------
+1 | R | N | B |   | K |   | N | R | 1
+  ---------------------------------
+    A   B   C   D   E   F   G   H  
+";
+    assert_eq!(board.render(&options), expected);
+}
+
+#[test]
+fn renders_flipped_in_color() {
+    let board = Board::from_fen(MATE).unwrap();
+    let options = RenderOptions {
+        unicode: true,
+        colors: true,
+        flipped: true,
+        highlight_check: true,
+        ..Default::default()
+    };
+    let text = board.render(&options);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 9);
+    assert_eq!(lines[0], "1 \x1b[48;5;180m\x1b[1;97m ♖ \x1b[0m\x1b[48;5;137m\x1b[1;97m ♘ \x1b[0m\x1b[48;5;180m\x1b[1;30m   \x1b[0m\x1b[48;5;137m\x1b[1;97m ♔ \x1b[0m\x1b[48;5;180m\x1b[1;30m   \x1b[0m\x1b[48;5;137m\x1b[1;97m ♗ \x1b[0m\x1b[48;5;180m\x1b[1;97m ♘ \x1b[0m\x1b[48;5;137m\x1b[1;97m ♖ \x1b[0m");
+    // the checked king sits on red
+    assert!(lines[7].contains("\x1b[48;5;160m\x1b[1;30m ♚ "));
+    assert_eq!(lines[8], "   H  G  F  E  D  C  B  A ");
+}
@@ -0,0 +1,86 @@
+use rust_chess::*;
+
+fn san(fen: &str, coordinates: &str) -> String {
+    let board = Board::from_fen(fen).unwrap();
+    let mv = board.parse_coordinates(coordinates).unwrap();
+    board.move_to_san(&mv)
+}
+
+#[test]
+fn disambiguates_by_file_rank_or_both() {
+    let knights = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(san(knights, "b1d2"), "Nbd2");
+    assert_eq!(san(knights, "f1d2"), "Nfd2");
+    assert_eq!(san(knights, "b1c3"), "Nc3");
+
+    let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(san(rooks, "a1a3"), "R1a3");
+    assert_eq!(san(rooks, "a5a3"), "R5a3");
+
+    let queens = "K1k5/8/8/8/4Q2Q/8/8/7Q w - - 0 1";
+    assert_eq!(san(queens, "h4e1"), "Qh4e1");
+    assert_eq!(san(queens, "e4e1"), "Qee1");
+    assert_eq!(san(queens, "h1e1"), "Q1e1");
+
+    // a pinned knight cannot go to e2, so the other one needs no file
+    assert_eq!(san("4k3/8/8/b7/8/2N5/8/4K1N1 w - - 0 1", "g1e2"), "Ne2");
+}
+
+#[test]
+fn promotions_and_captures() {
+    let fen = "3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(san(fen, "e7e8q"), "e8=Q+");
+    assert_eq!(san(fen, "e7e8r"), "e8=R+");
+    assert_eq!(san(fen, "e7d8n"), "exd8=N");
+    assert_eq!(san(fen, "e7d8b"), "exd8=B");
+    assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+}
+
+#[test]
+fn check_and_mate_suffixes() {
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    assert_eq!(san("6k1/5pp1/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8+");
+    assert_eq!(san("6k1/5pp1/8/8/8/8/8/R5K1 w - - 0 1", "a1a7"), "Ra7");
+    assert_eq!(san("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1"), "O-O-O+");
+    assert_eq!(san("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"), "O-O");
+}
+
+#[test]
+fn reads_what_it_writes() {
+    let fens = [
+        STARTING_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "K1k5/8/8/8/4Q2Q/8/8/7Q w - - 0 1",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        for mv in board.legal_moves() {
+            let text = board.move_to_san(&mv);
+            assert_eq!(board.parse_san(&text), Ok(mv), "{} in {}", text, fen);
+        }
+    }
+}
+
+#[test]
+fn reads_loose_notation() {
+    let board = Board::from_fen("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let underpromotion = board.parse_coordinates("e7d8n").unwrap();
+    assert_eq!(board.parse_san("exd8=N"), Ok(underpromotion));
+    assert_eq!(board.parse_san("ed8N"), Ok(underpromotion));
+    assert_eq!(board.parse_san("exd8N!?"), Ok(underpromotion));
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert_eq!(board.parse_san("0-0"), board.parse_san("O-O"));
+    assert!(board.parse_san("O-O").is_ok());
+}
+
+#[test]
+fn rejects_bad_san() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+    assert_eq!(board.parse_san("Nd2"), Err(MoveError::Ambiguous("Nd2".to_string())));
+    assert_eq!(board.parse_san("Nd3"), Err(MoveError::Illegal("Nd3".to_string())));
+    assert_eq!(board.parse_san("O-O"), Err(MoveError::Illegal("O-O".to_string())));
+    assert_eq!(board.parse_san("Zz9"), Err(MoveError::Unreadable("Zz9".to_string())));
+    assert_eq!(board.parse_san(""), Err(MoveError::Unreadable("".to_string())));
+}
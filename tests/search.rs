@@ -0,0 +1,89 @@
+use rust_chess::*;
+
+fn nodes(options: SearchOptions, depth: u32) -> u64 {
+    BENCH_POSITIONS
+        .iter()
+        .map(|fen| {
+            let board = Board::from_fen(fen).unwrap();
+            Searcher::with_options(options).search(&board, SearchLimits::depth(depth)).nodes
+        })
+        .sum()
+}
+
+#[test]
+fn ordering_cuts_the_bench_nodes_down() {
+    let unordered = nodes(SearchOptions::unordered(), 3);
+    let ordered = nodes(SearchOptions::default(), 3);
+    assert!(ordered * 2 < unordered, "ordered {} against unordered {}", ordered, unordered);
+}
+
+#[test]
+fn finds_short_mates() {
+    let back_rank = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+    let result = Searcher::new().search(&back_rank, SearchLimits::depth(3));
+    assert_eq!(result.mate_in(), Some(1));
+    assert_eq!(back_rank.move_to_san(&result.best_move.unwrap()), "Rd8#");
+
+    let scholars = Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+    let result = Searcher::new().search(&scholars, SearchLimits::depth(3));
+    assert_eq!(scholars.move_to_san(&result.best_move.unwrap()), "Qxf7#");
+}
+
+#[test]
+fn multi_pv_ranks_the_best_moves() {
+    let back_rank = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+    let mut searcher = Searcher::new();
+    searcher.set_multi_pv(3);
+    let lines = searcher.search_lines(&back_rank, &[], SearchLimits::depth(3));
+    assert_eq!(lines.len(), 3);
+    assert_eq!(back_rank.move_to_san(&lines[0].best_move.unwrap()), "Rd8#");
+    assert_eq!(lines[0].mate_in(), Some(1));
+    for (rank, line) in lines.iter().enumerate() {
+        assert_eq!(line.rank, rank + 1);
+        assert_eq!(line.pv.first(), line.best_move.as_ref());
+        assert!(lines[..rank].iter().all(|better| better.best_move != line.best_move && better.score >= line.score));
+    }
+
+    // no more lines than there are moves
+    let cornered = Board::from_fen("k7/8/8/8/8/8/8/2R4K b - - 0 1").unwrap();
+    searcher.set_multi_pv(5);
+    let lines = searcher.search_lines(&cornered, &[], SearchLimits::depth(3));
+    assert_eq!(lines.len(), cornered.legal_moves().len());
+}
+
+// plain alpha-beta, each refinement on its own, and all of them but one
+fn option_sets() -> Vec<(String, SearchOptions)> {
+    let mut sets = vec![("plain".to_string(), SearchOptions::plain()), ("all".to_string(), SearchOptions::default())];
+    for name in SearchOptions::NAMES {
+        let mut only = SearchOptions::plain();
+        only.set(name, true);
+        sets.push((format!("only {}", name), only));
+        let mut without = SearchOptions::default();
+        without.set(name, false);
+        sets.push((format!("without {}", name), without));
+    }
+    sets
+}
+
+fn assert_mate(fen: &str, key: &str, moves: i32, depth: u32) {
+    let board = Board::from_fen(fen).unwrap();
+    for (name, options) in option_sets() {
+        let result = Searcher::with_options(options).search(&board, SearchLimits::depth(depth));
+        assert_eq!(board.move_to_san(&result.best_move.unwrap()), key, "{}", name);
+        assert_eq!(result.mate_in(), Some(moves), "{}", name);
+    }
+}
+
+#[test]
+fn prunings_keep_a_mate() {
+    // 1.Rh1 threatens Rh8#, which the king cannot get away from
+    assert_mate("6k1/8/5K2/8/8/8/8/R7 w - - 0 1", "Rh1", 2, 6);
+}
+
+#[test]
+fn null_move_keeps_a_zugzwang() {
+    // Morphy: after 1.Ra6 there is no threat, every black move allows a
+    // mate, so passing would be black's best. The depth leaves room for the
+    // shallower search that checks a null move cut to see the mate.
+    assert_mate("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1", "Ra6", 2, 10);
+}
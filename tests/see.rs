@@ -0,0 +1,67 @@
+use rust_chess::*;
+
+const PAWN: i32 = 100;
+const KNIGHT: i32 = 320;
+const BISHOP: i32 = 330;
+const ROOK: i32 = 500;
+const QUEEN: i32 = 900;
+
+#[test]
+fn values() {
+    assert_eq!(piece_value(PieceType::Pawn(false)), PAWN);
+    assert_eq!(piece_value(PieceType::Knight), KNIGHT);
+    assert_eq!(piece_value(PieceType::Bishop), BISHOP);
+    assert_eq!(piece_value(PieceType::Rook(false)), ROOK);
+    assert_eq!(piece_value(PieceType::Queen), QUEEN);
+}
+
+fn see(fen: &str, san: &str) -> i32 {
+    let board = Board::from_fen(fen).unwrap();
+    let mv = board.parse_san(san).unwrap();
+    board.see(&mv)
+}
+
+#[test]
+fn undefended_pawn() {
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "Rxe5"), PAWN);
+}
+
+#[test]
+fn losing_capture() {
+    // Nxe5 Nxe5 Rxe5 Bxe5 Qxe5 Qxe5, black's queen x-rays through the bishop
+    let score = see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "Nxe5");
+    assert_eq!(score, PAWN - KNIGHT);
+    assert!(score < 0);
+}
+
+#[test]
+fn x_rays_decide_the_exchange() {
+    // the rook behind takes back, so black should not recapture
+    assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "Rxe5"), PAWN);
+    assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/6K1 w - - 0 1", "Rxe5"), PAWN - ROOK);
+
+    // the queen behind the bishop turns a won knight into a lost exchange
+    let without_queen = see("6k1/8/2b5/3n4/8/8/3R4/3R2K1 w - - 0 1", "Rxd5");
+    assert_eq!(without_queen, KNIGHT - ROOK + BISHOP);
+    let with_queen = see("6k1/1q6/2b5/3n4/8/8/3R4/3R2K1 w - - 0 1", "Rxd5");
+    assert_eq!(with_queen, KNIGHT - ROOK);
+}
+
+#[test]
+fn kings_and_pins() {
+    // the king takes back when nothing guards the square and not otherwise
+    assert_eq!(see("8/8/5k2/4p3/8/8/4R3/4K3 w - - 0 1", "Rxe5"), PAWN - ROOK);
+    assert_eq!(see("8/8/5k2/4p3/2N5/8/4R3/4K3 w - - 0 1", "Rxe5"), PAWN);
+
+    // pins are not looked at, the pinned d6 pawn still counts as taking back
+    assert_eq!(see("8/4k3/3p4/4p3/1B6/8/8/4RK2 w - - 0 1", "Rxe5"), PAWN - ROOK);
+}
+
+#[test]
+fn quiet_moves_and_promotions() {
+    assert_eq!(see(STARTING_FEN, "e4"), 0);
+    // a pawn stepping where a pawn takes it, with and without a pawn behind
+    assert_eq!(see("4k3/8/8/3p4/8/8/4P3/4K3 w - - 0 1", "e4"), -PAWN);
+    assert_eq!(see("4k3/8/8/3p4/8/3P4/4P3/4K3 w - - 0 1", "e4"), 0);
+    assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q"), QUEEN - PAWN);
+}
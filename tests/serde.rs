@@ -0,0 +1,72 @@
+#![cfg(feature = "serde")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_chess::*;
+
+#[test]
+fn board_is_stored_as_its_fen() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 b Q - 37 52").unwrap();
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(json, "\"4k3/8/8/8/8/8/8/R3K3 b Q - 37 52\"");
+    assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+    assert!(serde_json::from_str::<Board>("\"8/8 w - -\"").is_err());
+
+    let board = Board::from_fen("8/8/8/4k3/8/8/8/4K3 b - - 0 200").unwrap();
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(serde_json::from_str::<Board>(&json).unwrap().to_fen(), board.to_fen());
+}
+
+#[test]
+fn game_round_trips_through_json() {
+    let mut game = Game::new();
+    game.white = "Anderssen".to_string();
+    game.black = "Kieseritzky".to_string();
+    game.set_tag("ECO", "C33");
+    let time = Arc::new(ManualTime::new());
+    let mut clock = Clock::with_time_source(TimeControl::sudden_death(Duration::from_secs(300)), time.clone());
+    clock.start(Color::White);
+    game.clock = Some(clock);
+    for text in ["e4", "e5", "f4", "exf4"] {
+        time.advance(Duration::from_secs(5));
+        game.play_str(text).unwrap();
+    }
+    game.comment("the King's Gambit accepted");
+    game.resign(Color::White);
+
+    let json = serde_json::to_string(&game).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["start"], STARTING_FEN);
+    assert_eq!(value["result"], "0-1");
+    assert_eq!(value["termination"], "Resignation");
+    assert_eq!(value["moves"][3]["san"], "exf4");
+    assert_eq!(value["moves"][3]["clock"]["secs"], 290);
+
+    let read: Game = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.white, "Anderssen");
+    assert_eq!(read.tag("ECO"), Some("C33"));
+    assert_eq!(read.moves(), game.moves());
+    assert_eq!(read.board(), game.board());
+    assert_eq!(read.result(), GameResult::BlackWins);
+    assert_eq!(read.termination(), Some(Termination::Resignation));
+}
+
+#[test]
+fn game_with_an_illegal_move_is_rejected() {
+    let mut game = Game::new();
+    game.play_str("e4").unwrap();
+    let json = serde_json::to_string(&game).unwrap();
+    let tampered = json.replace(&format!("\"start\":\"{}\"", STARTING_FEN), "\"start\":\"4k3/8/8/8/8/8/8/4K3 w - - 0 1\"");
+    assert_ne!(json, tampered);
+    assert!(serde_json::from_str::<Game>(&tampered).is_err());
+}
+
+#[test]
+fn result_is_stored_the_way_pgn_writes_it() {
+    for result in [GameResult::WhiteWins, GameResult::BlackWins, GameResult::Draw, GameResult::Ongoing] {
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(json, format!("\"{}\"", result));
+        assert_eq!(serde_json::from_str::<GameResult>(&json).unwrap(), result);
+    }
+}
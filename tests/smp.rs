@@ -0,0 +1,28 @@
+use rust_chess::*;
+
+fn search(fen: &str, depth: u32, threads: usize) -> SearchResult {
+    let board = Board::from_fen(fen).unwrap();
+    let mut searcher = Searcher::new();
+    searcher.set_threads(threads);
+    searcher.search(&board, SearchLimits::depth(depth))
+}
+
+// Positions with one way to mate, so however the threads interleave the
+// answer is the same.
+#[test]
+fn more_threads_find_the_same_mates() {
+    let mates = [
+        ("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1", 3, 1),
+        ("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", 3, 1),
+        ("r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1", 4, 2),
+    ];
+    for (fen, depth, moves) in mates {
+        let alone = search(fen, depth, 1);
+        assert_eq!(alone.mate_in(), Some(moves), "{}", fen);
+        for _ in 0..3 {
+            let together = search(fen, depth, 4);
+            assert_eq!(together.best_move, alone.best_move, "{}", fen);
+            assert_eq!(together.mate_in(), Some(moves), "{}", fen);
+        }
+    }
+}
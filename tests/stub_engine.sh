@@ -0,0 +1,55 @@
+#!/bin/sh
+# A stand-in UCI engine for tests/uci_client.rs. It knows two positions and
+# plays e2e4 in any other, legal or not. The Behaviour option makes it answer
+# go at once (normal), only once told to stop (slow) or never (dead). With
+# UCI_Chess960 on it also says what position it was given.
+
+behaviour=normal
+chess960=false
+position=""
+
+answer() {
+    case "$position" in
+        "position startpos")
+            echo "info depth 1 multipv 1 score cp 25 nodes 20 time 1 pv e2e4 e7e5"
+            echo "info depth 1 multipv 2 score cp 20 nodes 20 time 1 pv d2d4"
+            echo "info depth 2 multipv 1 score cp 30 nodes 400 time 2 pv e2e4 e7e5 g1f3"
+            echo "info depth 2 multipv 2 score cp 15 nodes 400 time 2 pv d2d4 d7d5"
+            echo "bestmove e2e4 ponder e7e5"
+            ;;
+        "position fen 6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1")
+            echo "info depth 1 score mate 1 nodes 30 time 1 pv d1d8"
+            echo "bestmove d1d8"
+            ;;
+        *)
+            echo "bestmove e2e4"
+            ;;
+    esac
+}
+
+while read -r line; do
+    case "$line" in
+        uci)
+            echo "id name Stub Engine"
+            echo "id author the rust_chess tests"
+            echo "option name Hash type spin default 16 min 1 max 64"
+            echo "option name Behaviour type combo default normal var normal var slow var dead"
+            echo "option name Clear Hash type button"
+            echo "option name UCI_Chess960 type check default false"
+            echo "uciok"
+            ;;
+        isready) echo "readyok" ;;
+        "setoption name Behaviour value "*) behaviour=${line#setoption name Behaviour value } ;;
+        "setoption name UCI_Chess960 value "*) chess960=${line#setoption name UCI_Chess960 value } ;;
+        position*) position=$line ;;
+        go*)
+            echo "info string $line"
+            if [ "$chess960" = true ]; then echo "info string $position"; fi
+            if [ "$behaviour" = normal ]; then answer; fi
+            ;;
+        stop)
+            if [ "$behaviour" = slow ]; then answer; fi
+            ;;
+        quit) exit 0 ;;
+    esac
+done
@@ -0,0 +1,32 @@
+use rust_chess::*;
+
+const MATE: &str = "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4";
+
+// The whole image for one position, with coordinates, the last move's squares
+// highlighted and an arrow from the bishop to f7. Look at the file in a
+// browser before updating it.
+#[test]
+fn matches_snapshot() {
+    let board = Board::from_fen(MATE).unwrap();
+    let options = SvgOptions {
+        size: 360,
+        highlighted: vec![(3, 7), (1, 5)],
+        arrows: vec![((4, 2), (1, 5))],
+        ..Default::default()
+    };
+    assert_eq!(board.to_svg(&options), include_str!("data/scholars_mate.svg"));
+}
+
+#[test]
+fn flipped_without_coordinates() {
+    let board = Board::from_fen(MATE).unwrap();
+    let options = SvgOptions { size: 320, flipped: true, coordinates: false, ..Default::default() };
+    let svg = board.to_svg(&options);
+    assert!(!svg.contains("<text"));
+    // each square is 40 wide with no border, and h1 is in the top left corner
+    assert!(svg.contains(r##"<use xlink:href="#wr" href="#wr" transform="translate(0.00,0.00) scale(0.8889)"/>"##));
+    assert!(svg.contains(r##"<use xlink:href="#br" href="#br" transform="translate(280.00,280.00) scale(0.8889)"/>"##));
+    // one definition for each kind of piece on the board, used once for each piece
+    assert_eq!(svg.matches("<g id=").count(), 12);
+    assert_eq!(svg.matches("<use ").count(), 31);
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "syzygy")]
+
+use rust_chess::*;
+use std::env;
+use std::path::PathBuf;
+
+// The tables are not shipped with the crate, so these are ignored by default
+// and run with --ignored once the 3 piece tables are in SYZYGY_PATH or
+// tests/syzygy. Without them they fail rather than pass having checked nothing.
+fn open_tables() {
+    let dir = env::var_os("SYZYGY_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("tests/syzygy"));
+    match open_tablebases(&dir) {
+        Ok(count) if count > 0 => (),
+        _ => panic!("no Syzygy tables in {}", dir.display()),
+    }
+}
+
+#[test]
+#[ignore = "needs the 3 piece Syzygy tables in SYZYGY_PATH or tests/syzygy"]
+fn wdl_and_dtz() {
+    open_tables();
+    let queen_up = Board::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    assert_eq!(queen_up.probe_wdl(), Some(Wdl::Win));
+    assert!(queen_up.probe_dtz().unwrap() > 0);
+
+    let queen_down = Board::from_fen("8/8/8/4k3/8/8/8/KQ6 b - - 0 1").unwrap();
+    assert_eq!(queen_down.probe_wdl(), Some(Wdl::Loss));
+    assert!(queen_down.probe_dtz().unwrap() < 0);
+
+    let lone_knight = Board::from_fen("8/8/8/4k3/8/8/8/KN6 w - - 0 1").unwrap();
+    assert_eq!(lone_knight.probe_wdl(), Some(Wdl::Draw));
+    assert_eq!(lone_knight.probe_dtz(), Some(0));
+
+    // nothing covers the start position or anything that can still castle
+    assert_eq!(Board::default().probe_wdl(), None);
+    let castling = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert_eq!(castling.probe_wdl(), None);
+}
+
+#[test]
+#[ignore = "needs the 3 piece Syzygy tables in SYZYGY_PATH or tests/syzygy"]
+fn search_keeps_the_tablebase_win() {
+    open_tables();
+    let board = Board::from_fen("8/8/8/4k3/8/8/8/KR6 w - - 0 1").unwrap();
+    let result = Searcher::new().search(&board, SearchLimits::depth(4));
+    // answered at the root, from the tables
+    assert_eq!(result.score, TABLEBASE_WIN);
+    assert_eq!(result.depth, 1);
+
+    let mut after = board.clone();
+    assert!(after.play_move(&result.best_move.unwrap()));
+    assert_eq!(after.probe_wdl(), Some(Wdl::Loss));
+}
+
+#[test]
+#[ignore = "needs the 3 piece Syzygy tables in SYZYGY_PATH or tests/syzygy"]
+fn search_probes_the_tables_after_a_capture() {
+    open_tables();
+    // four pieces are too many for the root probe, taking the knight leaves
+    // three that the tree probe knows are won
+    let board = Board::from_fen("8/8/8/4k3/1n6/8/8/KR6 w - - 0 1").unwrap();
+    assert_eq!(board.probe_wdl(), None);
+    let result = Searcher::new().search(&board, SearchLimits::depth(2));
+    assert_eq!(board.move_to_san(&result.best_move.unwrap()), "Rxb4");
+    assert_eq!(result.score, TABLEBASE_WIN - 1);
+}
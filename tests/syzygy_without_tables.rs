@@ -0,0 +1,41 @@
+#![cfg(feature = "syzygy")]
+
+use rust_chess::*;
+
+// The open tables are shared by the whole program, so these live in their own
+// test binary where nothing opens any: the probes answer nothing and the
+// search runs exactly as it does with them turned off.
+#[test]
+fn probes_fall_through_without_tables() {
+    assert_eq!(tablebase_pieces(), 0);
+    let board = Board::from_fen("8/8/8/4k3/8/8/8/KR6 w - - 0 1").unwrap();
+    assert_eq!(board.probe_wdl(), None);
+    assert_eq!(board.probe_dtz(), None);
+
+    let search = |use_tablebases: bool| {
+        let mut searcher = Searcher::new();
+        searcher.use_tablebases = use_tablebases;
+        let result = searcher.search(&board, SearchLimits::depth(4));
+        (result.best_move, result.score, result.depth, result.nodes, result.pv)
+    };
+    assert_eq!(search(true), search(false));
+}
+
+#[test]
+fn opening_a_directory_without_tables() {
+    let dir = std::env::temp_dir().join(format!("rust_chess_no_tables_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let opened = open_tablebases(&dir);
+    std::fs::remove_dir(&dir).unwrap();
+    assert_eq!(opened.unwrap(), 0);
+    assert_eq!(tablebase_pieces(), 0);
+    assert!(open_tablebases(dir.join("missing")).is_err());
+}
+
+#[test]
+fn wdl_orders_from_the_loss_up() {
+    let order = [Wdl::Loss, Wdl::BlessedLoss, Wdl::Draw, Wdl::CursedWin, Wdl::Win];
+    assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+    let names: Vec<String> = order.iter().map(Wdl::to_string).collect();
+    assert_eq!(names, ["loss", "blessed loss", "draw", "cursed win", "win"]);
+}
@@ -0,0 +1,99 @@
+use rust_chess::*;
+
+#[test]
+fn squares_round_trip() {
+    for rank in 0..8 {
+        for file in 0..8 {
+            let square = Square { rank, file };
+            let text = square.to_string();
+            assert_eq!(text.parse::<Square>().unwrap(), square);
+        }
+    }
+    assert_eq!("a8".parse::<Square>().unwrap(), Square { rank: 0, file: 0 });
+    assert_eq!(" h1 ".parse::<Square>().unwrap(), Square { rank: 7, file: 7 });
+    assert_eq!(Square::from((4, 4)).to_string(), "e4");
+    for bad in ["", "e", "e9", "i4", "e44", "4e"] {
+        assert_eq!(bad.parse::<Square>(), Err(ParseError::InvalidSquare(bad.to_string())));
+    }
+}
+
+#[test]
+fn pieces_colors_and_types_round_trip() {
+    for text in ["K", "Q", "R", "B", "N", "P", "k", "q", "r", "b", "n", "p"] {
+        assert_eq!(text.parse::<Piece>().unwrap().to_string(), text);
+    }
+    assert_eq!("♞".parse::<Piece>().unwrap(), "n".parse::<Piece>().unwrap());
+    assert_eq!("♕".parse::<Piece>().unwrap(), "Q".parse::<Piece>().unwrap());
+    assert!(matches!("x".parse::<Piece>(), Err(ParseError::InvalidPiece(_))));
+    assert!(matches!("Kq".parse::<Piece>(), Err(ParseError::InvalidPiece(_))));
+
+    for color in [Color::White, Color::Black] {
+        assert_eq!(color.to_string().parse::<Color>().unwrap(), color);
+    }
+    assert_eq!("b".parse::<Color>().unwrap(), Color::Black);
+    assert!(matches!("red".parse::<Color>(), Err(ParseError::InvalidColor(_))));
+
+    assert_eq!("knight".parse::<PieceType>().unwrap(), PieceType::Knight);
+    assert_eq!("R".parse::<PieceType>().unwrap(), PieceType::Rook(false));
+    assert!(matches!("dragon".parse::<PieceType>(), Err(ParseError::InvalidPieceType(_))));
+}
+
+#[test]
+fn diagrams_read_back_into_the_same_position() {
+    let fens = [
+        STARTING_FEN,
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        let read: Board = board.to_string().parse().unwrap();
+        assert_eq!(read.to_fen(), fen);
+
+        // flipped and highlighted diagrams read the same
+        let options = RenderOptions {
+            unicode: true,
+            flipped: true,
+            coordinates_both_sides: true,
+            last_move: Some(((6, 4), (4, 4))),
+            highlight_check: true,
+            ..Default::default()
+        };
+        let read: Board = board.render(&options).parse().unwrap();
+        assert_eq!(read.to_fen(), fen);
+    }
+}
+
+#[test]
+fn diagrams_set_the_moved_flags_from_the_squares() {
+    // the rooks and king are at home so they can castle, the pawn on e4 has moved
+    let board = Board::from_fen("r3k2r/8/8/8/4P3/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let read: Board = board.to_string().parse().unwrap();
+    assert_eq!(read.to_fen(), "r3k2r/8/8/8/4P3/8/8/R3K2R w KQkq - 0 1");
+
+    let moved = Board::from_fen("1r2k3/8/8/8/8/8/8/R4K2 w - - 0 1").unwrap();
+    let read: Board = moved.to_string().parse().unwrap();
+    assert_eq!(read.to_fen(), "1r2k3/8/8/8/8/8/8/R4K2 w - - 0 1");
+}
+
+#[test]
+fn bad_diagrams_are_rejected() {
+    let diagram = Board::default().to_string();
+
+    let short: String = diagram.lines().filter(|line| !line.starts_with('3')).collect::<Vec<_>>().join("\n");
+    assert_eq!(short.parse::<Board>(), Err(ParseError::InvalidDiagram("expected 8 ranks".to_string())));
+
+    let doubled = diagram.replace("3 |", "4 |");
+    assert_eq!(doubled.parse::<Board>(), Err(ParseError::InvalidDiagram("rank 4 appears twice".to_string())));
+
+    let narrow = diagram.replace("2 | P | P | P | P | P | P | P | P |", "2 | P | P | P");
+    assert_eq!(
+        narrow.parse::<Board>(),
+        Err(ParseError::InvalidDiagram("rank 2 does not have 8 squares".to_string()))
+    );
+
+    let unknown = diagram.replace("| q |", "| x |");
+    assert_eq!(unknown.parse::<Board>(), Err(ParseError::InvalidPiece("x".to_string())));
+
+    assert!("".parse::<Board>().is_err());
+}
@@ -0,0 +1,132 @@
+use rust_chess::*;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// A clock that moves on a millisecond every time it is read, so a search
+// sees time pass at the same points on every run.
+#[derive(Debug, Default)]
+struct Ticking {
+    millis: AtomicU64,
+}
+
+impl TimeSource for Ticking {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.millis.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn allocates_from_the_clock() {
+    // a minute with a second a move: a thirtieth of it plus most of the increment
+    let manager = TimeManager::new(ms(60_000), ms(1000), None, ms(50));
+    assert_eq!(manager.soft(), ms(59_950) / 30 + ms(750));
+    assert_eq!(manager.hard(), manager.soft() * 3);
+
+    // the moves left before the control split what there is
+    let manager = TimeManager::new(ms(60_000), Duration::ZERO, Some(10), ms(50));
+    assert_eq!(manager.soft(), ms(5995));
+
+    // and the last move before it may use almost all of it
+    let manager = TimeManager::new(ms(10_000), Duration::ZERO, Some(1), ms(50));
+    assert_eq!(manager.hard(), ms(9950) * 9 / 10);
+
+    let manager = TimeManager::fixed(ms(1000), ms(50));
+    assert_eq!((manager.soft(), manager.hard()), (ms(950), ms(950)));
+}
+
+#[test]
+fn never_plans_to_flag() {
+    for remaining in [0, 10, 49, 50, 51, 100, 1000, 30_000, 600_000] {
+        for increment in [0, 100, 2000, 30_000] {
+            for moves_to_go in [None, Some(1), Some(2), Some(40)] {
+                let manager = TimeManager::new(ms(remaining), ms(increment), moves_to_go, ms(50));
+                assert!(manager.soft() <= manager.hard());
+                assert!(manager.hard() <= ms(remaining).saturating_sub(ms(50)));
+            }
+        }
+    }
+}
+
+#[test]
+fn thinks_longer_while_the_best_move_changes() {
+    let mut manager = TimeManager::new(ms(30_000), Duration::ZERO, None, ms(50));
+    let soft = manager.soft();
+    assert!(manager.should_stop(soft));
+
+    manager.iteration_done(true);
+    assert!(!manager.should_stop(soft));
+    assert!(manager.should_stop(soft * 3 / 2));
+    manager.iteration_done(true);
+    manager.iteration_done(true);
+    manager.iteration_done(true);
+    assert!(!manager.should_stop(soft * 2));
+    // never past the hard limit however unsettled it is
+    assert!(manager.should_stop(manager.hard()));
+    assert!(manager.must_stop(manager.hard()));
+
+    // and comes back down once it settles
+    for _ in 0..10 {
+        manager.iteration_done(false);
+    }
+    assert!(manager.should_stop(soft));
+}
+
+#[test]
+fn search_stops_inside_the_hard_limit() {
+    let board = Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+    let manager = TimeManager::new(ms(2000), Duration::ZERO, None, ms(50));
+    let mut searcher = Searcher::new();
+    searcher.set_time_source(Arc::new(Ticking::default()));
+    let result = searcher.search(&board, SearchLimits::clock(manager));
+    assert!(result.best_move.is_some());
+    assert!(result.depth >= 1);
+    assert!(result.time <= manager.hard() + ms(5), "{:?} against {:?}", result.time, manager.hard());
+
+    // with no time at all there is still a move to play
+    let result = searcher.search(&board, SearchLimits::clock(TimeManager::new(ms(10), ms(0), None, ms(50))));
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn uci_go_answers_with_a_move() {
+    let mut uci = Uci::new(Vec::new());
+    uci.set_time_source(Arc::new(Ticking::default()));
+    let input = "uci\nisready\nposition startpos moves e2e4 e7e5\ngo wtime 1000 btime 1000 winc 10 binc 10\n\
+                 position fen 6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1\ngo movetime 500\n";
+    let output = String::from_utf8(uci.run(Cursor::new(input))).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines.contains(&"uciok"));
+    assert!(lines.contains(&"readyok"));
+    let best: Vec<&str> = lines.iter().filter(|line| line.starts_with("bestmove")).copied().collect();
+    assert_eq!(best.len(), 2);
+    assert_eq!(best[1], "bestmove d1d8");
+    assert!(lines.iter().any(|line| line.starts_with("info depth 1 score mate 1")));
+}
+
+#[test]
+fn uci_loads_evaluation_weights() {
+    let path = std::env::temp_dir().join(format!("rust_chess_uci_eval_{}.txt", std::process::id()));
+    std::fs::write(&path, "# a queen worth a rook\npiece_values 100 320 330 500 500\n").unwrap();
+    let score = |options: &str| -> String {
+        let input = format!("{}position fen 4k3/8/8/8/8/8/8/3QK3 w - - 0 1\ngo depth 1\n", options);
+        let output = String::from_utf8(Uci::new(Vec::new()).run(Cursor::new(input))).unwrap();
+        let info = output.lines().find(|line| line.starts_with("info depth 1 ")).unwrap().to_string();
+        info.split_whitespace().skip_while(|word| *word != "cp").nth(1).unwrap().to_string()
+    };
+    let built_in = score("");
+    let loaded = score(&format!("setoption name EvalFile value {}\n", path.display()));
+    let reset = score(&format!("setoption name EvalFile value {}\nsetoption name EvalFile value <empty>\n", path.display()));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.parse::<i32>().unwrap(), built_in.parse::<i32>().unwrap() - 400);
+    assert_eq!(reset, built_in);
+
+    let input = Cursor::new("setoption name EvalFile value /no/such/weights.txt\n");
+    let output = String::from_utf8(Uci::new(Vec::new()).run(input)).unwrap();
+    assert!(output.starts_with("info string could not read /no/such/weights.txt"));
+}
@@ -0,0 +1,83 @@
+use rust_chess::*;
+
+#[test]
+fn evaluation_weights_round_trip() {
+    let params = EvalParams::default();
+    assert_eq!(EvalParams::from_slice(&params.to_vec()), Ok(params.clone()));
+    let too_few = EvalParams::from_slice(&[0; 5]).unwrap_err();
+    assert_eq!(too_few.to_string(), format!("bad evaluation weights: there are 5 weights, not {}", EvalParams::COUNT));
+    assert_eq!(params.to_string().parse::<EvalParams>().unwrap(), params);
+    // the rust source is what eval.rs has
+    assert!(include_str!("../src/eval.rs").contains(&params.to_rust()));
+
+    // weights left out keep their values, and wrong ones are refused
+    let knights: EvalParams = "# heavier knights\npiece_values 100 400 330 500 900".parse().unwrap();
+    assert_eq!(knights.piece_values, [100, 400, 330, 500, 900]);
+    assert_eq!(knights.king_endgame_table, params.king_endgame_table);
+    assert!("piece_values 100 400".parse::<EvalParams>().is_err());
+    assert!("pawn_values 1 2 3 4 5".parse::<EvalParams>().is_err());
+
+    let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    assert_eq!(evaluate_with(&board, &params), evaluate(&board));
+    let white_knights = Board::from_fen("4k3/8/8/8/8/8/8/1N2K1N1 b - - 0 1").unwrap();
+    assert_eq!(evaluate_with(&white_knights, &knights), evaluate(&white_knights) - 160);
+}
+
+#[test]
+fn reads_positions_labelled_with_results() {
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 w - -";
+    let result = |line: &str| parse_labelled(line).unwrap().1;
+    assert_eq!(result(&format!("{} 0 1 1-0", fen)), 1.0);
+    assert_eq!(result(&format!("{} 0-1", fen)), 0.0);
+    assert_eq!(result(&format!("{} [0.5]", fen)), 0.5);
+    assert_eq!(result(&format!("{} c9 \"1/2-1/2\";", fen)), 0.5);
+    assert_eq!(result(&format!("{} id \"x\"; c9 \"1-0\";", fen)), 1.0);
+
+    // a full fen with no result is not one labelled 1
+    assert!(matches!(parse_labelled(&format!("{} 0 1", fen)), Err(TuneError::NoResult(_))));
+    assert!(matches!(parse_labelled("4k3/8/8 w - - 1-0"), Err(TuneError::BadPosition(_))));
+    assert_eq!(read_labelled(&format!("# comment\n\n{} 1-0\n", fen)).len(), 1);
+}
+
+#[test]
+fn tuning_brings_the_evaluation_closer_to_the_results() {
+    // an extra knight wins, an extra pawn draws
+    let labelled = [
+        ("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1", 1.0),
+        ("1n2k3/8/8/8/8/8/8/4K3 w - - 0 1", 0.0),
+        ("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1", 0.5),
+        ("4k3/3p4/8/8/8/8/8/4K3 b - - 0 1", 0.5),
+        ("4k3/8/8/8/8/8/8/2N1K3 b - - 0 1", 1.0),
+    ];
+    let positions: Vec<(Board, f64)> = labelled
+        .iter()
+        .map(|(fen, result)| (Board::from_fen(fen).unwrap(), *result))
+        .collect();
+    let mut tuner = Tuner::new(&positions);
+    assert_eq!(tuner.len(), 5);
+
+    // the error is the mean squared gap between result and expected score
+    tuner.k = 1.2;
+    let params = EvalParams::default();
+    let expected: f64 = positions
+        .iter()
+        .map(|(board, result)| {
+            let white = if board.side_to_move() == Color::White { 1 } else { -1 };
+            let score = (white * evaluate(board)) as f64;
+            (result - 1.0 / (1.0 + 10f64.powf(-1.2 * score / 400.0))).powi(2)
+        })
+        .sum::<f64>()
+        / 5.0;
+    assert!((tuner.error(&params) - expected).abs() < 1e-12);
+
+    let k = tuner.fit_k(&params);
+    assert!(k > 0.0 && k < 10.0);
+    let before = tuner.error(&params);
+    let mut errors = Vec::new();
+    let tuned = tuner.tune(&params, 50, 2.0, |_, error| errors.push(error));
+    assert_eq!(errors.len(), 50);
+    assert!(tuner.error(&tuned) < before);
+    // the pawn is worth less, and the queens, in none of the positions, are left alone
+    assert!(tuned.piece_values[0] < params.piece_values[0]);
+    assert_eq!(tuned.queen_table, params.queen_table);
+}
@@ -0,0 +1,120 @@
+#![cfg(unix)]
+
+use rust_chess::*;
+use std::time::{Duration, Instant};
+
+fn stub() -> UciEngine {
+    UciEngine::start(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/stub_engine.sh"), &[]).unwrap()
+}
+
+#[test]
+fn negotiates_options() {
+    let mut engine = stub();
+    assert_eq!(engine.name(), "Stub Engine");
+    assert_eq!(engine.author, "the rust_chess tests");
+    let names: Vec<&str> = engine.options.iter().map(|option| option.name.as_str()).collect();
+    assert_eq!(names, ["Hash", "Behaviour", "Clear Hash", "UCI_Chess960"]);
+    assert_eq!(engine.options[0].kind, "spin");
+    assert_eq!((engine.options[0].min, engine.options[0].max), (Some(1), Some(64)));
+    assert_eq!(engine.options[1].vars, ["normal", "slow", "dead"]);
+
+    assert!(engine.set_option("hash", "32").is_ok());
+    assert!(engine.set_option("Clear Hash", "").is_ok());
+    assert_eq!(engine.set_option("Threads", "2"), Err(UciError::UnknownOption("Threads".to_string())));
+    assert!(matches!(engine.set_option("Hash", "128"), Err(UciError::BadOptionValue { .. })));
+    assert!(matches!(engine.set_option("Behaviour", "fast"), Err(UciError::BadOptionValue { .. })));
+    assert!(engine.is_ready().is_ok());
+}
+
+#[test]
+fn reads_the_search() {
+    let mut engine = stub();
+    let start = Board::default();
+    let output = engine.go(&start, &[], &GoLimits::depth(2)).unwrap();
+    assert_eq!(output.best_move, start.parse_coordinates("e2e4").ok());
+    assert!(output.ponder.is_some());
+    assert_eq!(output.strings, ["go depth 2"]);
+    // the last line for each multipv rank
+    assert_eq!(output.lines.len(), 2);
+    assert_eq!((output.lines[0].depth, output.lines[0].score, output.lines[0].pv.len()), (2, 30, 3));
+    assert_eq!((output.lines[1].rank, output.lines[1].score), (2, 15));
+
+    let back_rank = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+    let output = engine.go(&back_rank, &[], &GoLimits::move_time(Duration::from_millis(50))).unwrap();
+    assert_eq!(output.lines[0].mate_in(), Some(1));
+    assert_eq!(output.strings, ["go movetime 50"]);
+
+    // the moves are sent after the start and the answer read on the board after them
+    let e4 = start.parse_coordinates("e2e4").unwrap();
+    assert_eq!(
+        engine.go(&start, &[e4], &GoLimits::depth(1)),
+        Err(UciError::BadMove("e2e4".to_string()))
+    );
+}
+
+#[test]
+fn stops_a_search_that_runs_over() {
+    let mut engine = stub();
+    engine.timeout = Duration::from_millis(200);
+    let start = Board::default();
+    let limits = GoLimits::move_time(Duration::from_millis(100));
+
+    engine.set_option("Behaviour", "slow").unwrap();
+    let output = engine.go(&start, &[], &limits).unwrap();
+    assert!(output.best_move.is_some());
+
+    engine.set_option("Behaviour", "dead").unwrap();
+    let started = Instant::now();
+    assert_eq!(engine.go(&start, &[], &limits), Err(UciError::Timeout("go".to_string())));
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // a search by depth has no time of its own, max_search_time stands in
+    engine.max_search_time = Duration::from_millis(100);
+    let started = Instant::now();
+    assert_eq!(engine.go(&start, &[], &GoLimits::depth(1)), Err(UciError::Timeout("go".to_string())));
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn sends_chess960_castling() {
+    let mut engine = stub();
+    // the rooks on b1 and b8 make it chess960, even though O-O looks normal
+    let start = Board::from_fen("1r2k2r/4pppp/8/8/8/8/4PPPP/1R2K2R w KQkq - 0 1").unwrap();
+    assert!(start.is_chess960() && !Board::default().is_chess960());
+    let mut board = start.clone();
+    let mut moves = Vec::new();
+    for san in ["O-O", "O-O"] {
+        let mv = board.parse_san(san).unwrap();
+        board.play_move(&mv);
+        moves.push(mv);
+    }
+    let output = engine.go(&start, &moves, &GoLimits::depth(1)).unwrap();
+    assert_eq!(
+        output.strings,
+        ["go depth 1", "position fen 1r2k2r/4pppp/8/8/8/8/4PPPP/1R2K2R w KQkq - 0 1 moves e1h1 e8h8"]
+    );
+
+    // and it is turned off again for a normal game
+    let output = engine.go(&Board::default(), &[], &GoLimits::depth(2)).unwrap();
+    assert_eq!(output.strings, ["go depth 2"]);
+}
+
+#[test]
+fn plays_a_game_on_the_clock() {
+    let mut engine = stub();
+    let mut searcher = SearcherPlayer::new("searcher", Searcher::new());
+    searcher.depth = Some(1);
+    let adjudication = Adjudication { max_moves: Some(1), ..Default::default() };
+    let time_control: TimeControl = "60+1".parse().unwrap();
+    let opening = Opening::new(Board::default());
+    let game = play_game(&mut engine, &mut searcher, &opening, Some(&time_control), &adjudication).unwrap();
+    assert_eq!(game.white, "Stub Engine");
+    assert_eq!(game.moves()[0].san, "e4");
+    assert_eq!(game.moves().len(), 2);
+    assert_eq!(game.termination(), Some(Termination::Adjudication));
+
+    // it gives up the game when its move is illegal
+    let game = play_game(&mut searcher, &mut engine, &opening, None, &Adjudication::default()).unwrap();
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert_eq!(game.termination(), Some(Termination::Abandoned));
+}
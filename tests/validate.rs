@@ -0,0 +1,138 @@
+use rust_chess::*;
+
+fn problems(fen: &str) -> Vec<PositionProblem> {
+    match Board::from_fen(fen) {
+        Err(FenError::IllegalPosition(error)) => error.problems,
+        other => panic!("{} gave {:?}", fen, other),
+    }
+}
+
+fn set(board: &mut Board, (rank, file): (usize, usize), piece_type: PieceType, color: Color) {
+    board.board[rank][file] = Some(Piece { piece_type, color });
+}
+
+#[test]
+fn legal_positions_pass() {
+    assert_eq!(Board::default().validate(), Ok(()));
+    for fen in [
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+        "4k3/8/8/8/8/8/8/4K2R w H - 0 1",
+        "4k3/8/3N4/8/8/8/8/3QK3 b - - 0 1",
+    ] {
+        assert_eq!(Board::from_fen(fen).unwrap().validate(), Ok(()), "{}", fen);
+    }
+}
+
+#[test]
+fn king_counts() {
+    assert_eq!(problems("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"), [PositionProblem::KingCount(Color::White, 2)]);
+    assert_eq!(problems("8/8/8/8/8/8/8/4K3 w - - 0 1"), [PositionProblem::KingCount(Color::Black, 0)]);
+}
+
+#[test]
+fn too_many_pawns_or_pieces() {
+    assert_eq!(
+        problems("4k3/8/8/P7/8/8/PPPPPPPP/4K3 w - - 0 1"),
+        [PositionProblem::TooManyPawns(Color::White, 9), PositionProblem::TooManyPieces(Color::White, 10)]
+    );
+    // eight pawns leave nothing to promote into the two extra queens
+    assert_eq!(
+        problems("4k3/8/8/8/8/8/PPPPPPPP/QQQ1K3 w - - 0 1"),
+        [PositionProblem::TooManyPieces(Color::White, 12)]
+    );
+    assert_eq!(Board::from_fen("4k3/8/8/8/8/8/PPPPPP2/QQQ1K3 w - - 0 1").unwrap().validate(), Ok(()));
+}
+
+#[test]
+fn pawns_on_the_back_ranks() {
+    assert_eq!(problems("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"), [PositionProblem::PawnOnBackRank((0, 0))]);
+    assert_eq!(problems("4k3/8/8/8/8/8/8/p3K3 w - - 0 1"), [PositionProblem::PawnOnBackRank((7, 0))]);
+}
+
+#[test]
+fn moved_flags_have_to_match_the_squares() {
+    let mut board = Board::default();
+    set(&mut board, (6, 0), PieceType::Pawn(true), Color::White);
+    set(&mut board, (3, 7), PieceType::Pawn(false), Color::Black);
+    board.board[1][7] = None;
+    assert_eq!(
+        board.validate().unwrap_err().problems,
+        [PositionProblem::PawnMovedFlag((3, 7)), PositionProblem::PawnMovedFlag((6, 0))]
+    );
+
+    let mut board = Board::default();
+    board.board[7][4] = None;
+    set(&mut board, (6, 4), PieceType::King(false), Color::White);
+    set(&mut board, (5, 0), PieceType::Rook(false), Color::White);
+    assert_eq!(
+        board.validate().unwrap_err().problems,
+        [PositionProblem::CastlingRookMoved((5, 0)), PositionProblem::CastlingKingMoved((6, 4))]
+    );
+}
+
+#[test]
+fn bad_castling_rights() {
+    // every right has to name a rook standing on its file
+    let bad = |fen: &str| Board::from_fen(fen).unwrap_err();
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4K3 w K - 0 1"), FenError::BadCastling(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/R3K3 w K - 0 1"), FenError::BadCastling(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/2N1K3 w C - 0 1"), FenError::BadCastling(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/8/4K2R w G - 0 1"), FenError::BadCastling(_)));
+    assert!(matches!(bad("r3k3/8/8/8/8/8/8/4K3 w h - 0 1"), FenError::BadCastling(_)));
+    assert!(matches!(bad("4k3/8/8/8/8/8/4K3/R7 w Q - 0 1"), FenError::BadCastling(_)));
+
+    // and there is only one rook to castle with on each side
+    assert_eq!(
+        problems("4k3/8/8/8/8/8/8/RR2K3 w QB - 0 1"),
+        [PositionProblem::CastlingRookTwice((7, 1))]
+    );
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/4K3 b kq - 0 1").unwrap();
+    set(&mut board, (0, 6), PieceType::Rook(false), Color::Black);
+    assert_eq!(board.validate().unwrap_err().problems, [PositionProblem::CastlingRookTwice((0, 6))]);
+}
+
+#[test]
+fn checks() {
+    // white is in check with black to move
+    assert_eq!(problems("4k3/8/8/8/8/8/8/4K2r b - - 0 1"), [PositionProblem::OpponentInCheck]);
+    // no single move gives check with two knights
+    assert_eq!(problems("4k3/8/3N1N2/8/8/8/8/4K3 b - - 0 1"), [PositionProblem::ImpossibleCheck(2)]);
+    assert_eq!(problems("4k3/8/3N1N2/8/8/8/8/1B2K3 b - - 0 1"), [PositionProblem::ImpossibleCheck(2)]);
+    assert_eq!(
+        problems("4k3/8/3N1N2/8/8/8/8/4R1K1 b - - 0 1"),
+        [PositionProblem::ImpossibleCheck(3)]
+    );
+}
+
+#[test]
+fn impossible_en_passant_squares() {
+    // no pawn in front of the square
+    assert_eq!(problems("4k3/8/8/8/8/8/8/4K3 b - e3 0 1"), [PositionProblem::BadEnPessant((5, 4))]);
+    // the pawn could not have come from e2
+    assert_eq!(problems("4k3/8/8/8/4P3/8/4P3/4K3 b - e3 0 1"), [PositionProblem::BadEnPessant((5, 4))]);
+    // the square is for the side to move to capture onto
+    assert!(matches!(
+        Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+        Err(FenError::BadEnPessant(_))
+    ));
+}
+
+#[test]
+fn every_problem_is_listed() {
+    let error = Board::from_fen("P3k3/8/8/8/8/8/8/r2KK3 b - - 0 1").unwrap_err();
+    assert_eq!(
+        error,
+        FenError::IllegalPosition(PositionError {
+            problems: vec![
+                PositionProblem::KingCount(Color::White, 2),
+                PositionProblem::PawnOnBackRank((0, 0)),
+                PositionProblem::OpponentInCheck,
+            ]
+        })
+    );
+    assert_eq!(
+        error.to_string(),
+        "illegal position: white has 2 kings instead of one, pawn on a8 is on a back rank, the side not to move is in check"
+    );
+}